@@ -1,4 +1,4 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt::Display};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 enum Packet {
@@ -29,6 +29,140 @@ impl PartialOrd for Packet {
     }
 }
 
+/// Print packets the same way they appear in the input
+impl Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Values(values) => {
+                let values = values.iter().map(usize::to_string).collect::<Vec<_>>();
+                write!(f, "{}", values.join(","))
+            }
+            Self::List(packets) => {
+                let packets = packets.iter().map(Packet::to_string).collect::<Vec<_>>();
+                write!(f, "[{}]", packets.join(","))
+            }
+        }
+    }
+}
+
+/// Single element of a packet, with runs of values split into separate integers.
+/// Used to trace comparisons the same way the puzzle describes them
+enum Element {
+    Integer(usize),
+    List(Vec<Element>),
+}
+
+impl Element {
+    /// Split runs of values into the surrounding list
+    fn from_packets(packets: &[Packet]) -> Vec<Element> {
+        packets
+            .iter()
+            .flat_map(|packet| match packet {
+                Packet::Values(values) => values.iter().map(|&v| Element::Integer(v)).collect(),
+                Packet::List(packets) => vec![Element::List(Element::from_packets(packets))],
+            })
+            .collect()
+    }
+}
+
+/// Packets are always lists, a single run of values becomes a list of integers
+impl From<&Packet> for Element {
+    fn from(packet: &Packet) -> Self {
+        match packet {
+            Packet::Values(values) => {
+                Element::List(values.iter().map(|&v| Element::Integer(v)).collect())
+            }
+            Packet::List(packets) => Element::List(Element::from_packets(packets)),
+        }
+    }
+}
+
+impl Display for Element {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::List(elements) => {
+                let elements = elements.iter().map(Element::to_string).collect::<Vec<_>>();
+                write!(f, "[{}]", elements.join(","))
+            }
+        }
+    }
+}
+
+/// Compare two elements while recording every step in the style of the puzzle text.
+/// Follows the same rules as the Ord-implementation
+fn compare_traced(
+    left: &Element,
+    right: &Element,
+    depth: usize,
+    trace: &mut Vec<String>,
+) -> Ordering {
+    let indent = "  ".repeat(depth);
+    let inner = "  ".repeat(depth + 1);
+
+    trace.push(format!("{indent}- Compare {left} vs {right}"));
+
+    match (left, right) {
+        (Element::Integer(l), Element::Integer(r)) => {
+            let ord = l.cmp(r);
+
+            match ord {
+                Ordering::Less => trace.push(format!(
+                    "{inner}- Left side is smaller, so inputs are in the right order"
+                )),
+                Ordering::Greater => trace.push(format!(
+                    "{inner}- Right side is smaller, so inputs are not in the right order"
+                )),
+                Ordering::Equal => {}
+            }
+
+            ord
+        }
+        (Element::List(left_list), Element::List(right_list)) => {
+            for (l, r) in left_list.iter().zip(right_list) {
+                let ord = compare_traced(l, r, depth + 1, trace);
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+
+            compare_lengths(left_list.len(), right_list.len(), &inner, trace)
+        }
+        // Mixed types, upgrade one side to a list and retry
+        (Element::List(_), Element::Integer(r)) => {
+            trace.push(format!(
+                "{inner}- Mixed types; convert right to [{right}] and retry comparison"
+            ));
+            let right = Element::List(vec![Element::Integer(*r)]);
+            compare_traced(left, &right, depth + 1, trace)
+        }
+        (Element::Integer(l), Element::List(_)) => {
+            trace.push(format!(
+                "{inner}- Mixed types; convert left to [{left}] and retry comparison"
+            ));
+            let left = Element::List(vec![Element::Integer(*l)]);
+            compare_traced(&left, right, depth + 1, trace)
+        }
+    }
+}
+
+/// Decide the order if all compared items were equal
+fn compare_lengths(left: usize, right: usize, indent: &str, trace: &mut Vec<String>) -> Ordering {
+    let ord = left.cmp(&right);
+
+    match ord {
+        Ordering::Less => trace.push(format!(
+            "{indent}- Left side ran out of items, so inputs are in the right order"
+        )),
+        Ordering::Greater => trace.push(format!(
+            "{indent}- Right side ran out of items, so inputs are not in the right order"
+        )),
+        Ordering::Equal => {}
+    }
+
+    ord
+}
+
 fn parse_line(input: &str) -> Packet {
     // Try parsing comma seperated integers
     let list = input
@@ -88,6 +222,12 @@ fn parse_line(input: &str) -> Packet {
     }
 }
 
+/// Parse a single packet, e.g. a divider packet
+fn parse_packet(input: &str) -> Packet {
+    // Use different symbols to seperate lists from values
+    parse_line(&input.trim().replace(",[", "|[").replace("],", "]|"))
+}
+
 fn parse_input(input: &str) -> Vec<Packet> {
     // Use different symbols to seperate lists from values
     let input = input.replace(",[", "|[").replace("],", "]|");
@@ -115,36 +255,157 @@ fn part_1(input: &str) -> String {
     res.to_string()
 }
 
-/// Compute the solution to part 2
-fn part_2(input: &str) -> String {
-    let mut parsed = parse_input(input);
+/// Compare every pair and return the comparison traces
+fn trace_pairs(input: &str) -> Vec<String> {
+    let packets = parse_input(input);
+
+    let mut trace = vec![];
+
+    for (idx, pair) in packets.chunks(2).enumerate() {
+        trace.push(format!("== Pair {} ==", idx + 1));
+        let (left, right) = (Element::from(&pair[0]), Element::from(&pair[1]));
+        compare_traced(&left, &right, 0, &mut trace);
+        trace.push(String::new());
+    }
+
+    trace
+}
 
-    // Create marker packets
-    let pack_a = parse_line("[[2]]");
-    let pack_b = parse_line("[[6]]");
+/// Sort all packets together with the divider packets.
+/// Returns the sorted packets and the (1-based) index of every divider
+fn sort_with_dividers(packets: Vec<Packet>, dividers: &[Packet]) -> (Vec<Packet>, Vec<usize>) {
+    // Insert markers into packets, tagged with the index of the divider
+    let mut tagged = packets
+        .into_iter()
+        .map(|packet| (packet, None))
+        .chain(
+            dividers
+                .iter()
+                .cloned()
+                .enumerate()
+                .map(|(i, d)| (d, Some(i))),
+        )
+        .collect::<Vec<_>>();
 
-    // Insert markers into packets
-    parsed.push(pack_a.clone());
-    parsed.push(pack_b.clone());
+    // Sort packets, equal packets keep their order
+    tagged.sort_by(|(a, _), (b, _)| a.cmp(b));
 
-    // Sort packets
-    parsed.sort();
+    // Find markers in sorted packets, so equal dividers get their own index
+    let indices = (0..dividers.len())
+        .map(|i| tagged.iter().position(|(_, tag)| *tag == Some(i)).unwrap() + 1)
+        .collect();
 
-    // Find markers in sorted packets
-    let idx_a = parsed.iter().position(|p| p == &pack_a).unwrap() + 1;
-    let idx_b = parsed.iter().position(|p| p == &pack_b).unwrap() + 1;
+    (
+        tagged.into_iter().map(|(packet, _)| packet).collect(),
+        indices,
+    )
+}
 
-    (idx_a * idx_b).to_string()
+/// Compute the solution to part 2
+fn part_2(input: &str, dividers: &[Packet]) -> String {
+    let (_, indices) = sort_with_dividers(parse_input(input), dividers);
+
+    indices.iter().product::<usize>().to_string()
 }
 
 fn main() {
     let input = include_str!("input.txt");
 
+    // Options: --trace, --sorted and any number of --divider <PACKET>
+    let mut show_trace = false;
+    let mut show_sorted = false;
+    let mut dividers = vec![];
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--trace" => show_trace = true,
+            "--sorted" => show_sorted = true,
+            "--divider" => {
+                let packet = args.next().expect("--divider requires a packet");
+                dividers.push(parse_packet(&packet));
+            }
+            _ => panic!("Unknown argument: {arg}"),
+        }
+    }
+
+    // Create default marker packets
+    if dividers.is_empty() {
+        dividers = vec![parse_packet("[[2]]"), parse_packet("[[6]]")];
+    }
+
     println!("Advent of Code 2022 - Day 13");
 
+    if show_trace {
+        trace_pairs(input)
+            .iter()
+            .for_each(|line| println!("{line}"));
+    }
+
+    if show_sorted {
+        let (sorted, _) = sort_with_dividers(parse_input(input), &dividers);
+        sorted.iter().for_each(|packet| println!("{packet}"));
+        println!();
+    }
+
     let part_1 = part_1(input);
-    let part_2 = part_2(input);
+    let part_2 = part_2(input, &dividers);
 
     println!("Part 1: {part_1}");
     println!("Part 2: {part_2}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trace of the second example pair, as shown in the puzzle
+    #[test]
+    fn trace_matches_puzzle() {
+        let trace = trace_pairs("[[1],[2,3,4]]\n[[1],4]\n\n[[[]]]\n[[]]\n");
+
+        let expected = [
+            "== Pair 1 ==",
+            "- Compare [[1],[2,3,4]] vs [[1],4]",
+            "  - Compare [1] vs [1]",
+            "    - Compare 1 vs 1",
+            "  - Compare [2,3,4] vs 4",
+            "    - Mixed types; convert right to [4] and retry comparison",
+            "    - Compare [2,3,4] vs [4]",
+            "      - Compare 2 vs 4",
+            "        - Left side is smaller, so inputs are in the right order",
+            "",
+            "== Pair 2 ==",
+            "- Compare [[[]]] vs [[]]",
+            "  - Compare [[]] vs []",
+            "    - Right side ran out of items, so inputs are not in the right order",
+            "",
+        ];
+
+        assert_eq!(trace, expected);
+    }
+
+    /// The traced comparison decides every pair of the input like the Ord-implementation
+    #[test]
+    fn trace_agrees_with_ord() {
+        let packets = parse_input(include_str!("input.txt"));
+
+        for pair in packets.chunks(2) {
+            let (left, right) = (Element::from(&pair[0]), Element::from(&pair[1]));
+            let traced = compare_traced(&left, &right, 0, &mut vec![]);
+
+            assert_eq!(traced, pair[0].cmp(&pair[1]));
+        }
+    }
+
+    /// Every copy of a divider gets its own index
+    #[test]
+    fn duplicate_dividers() {
+        let packets = parse_input("[1]\n[[3]]\n");
+        let divider = parse_packet("[[2]]");
+
+        let (_, indices) = sort_with_dividers(packets, &[divider.clone(), divider]);
+
+        assert_eq!(indices, [2, 3]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 type Pos = (isize, isize);
 
 #[derive(Debug)]
@@ -41,21 +43,53 @@ enum Material {
     Sand,
 }
 
+impl Material {
+    /// Color used for image exports
+    fn color(&self) -> [u8; 3] {
+        match self {
+            Material::Air => [20, 20, 30],
+            Material::Rock => [120, 110, 100],
+            Material::Sand => [230, 190, 90],
+        }
+    }
+}
+
 struct Simulation {
     grid: Vec<Vec<Material>>,
     origin: Pos,
+    sources: Vec<Pos>,
+    // Y-coordinate of the infinite floor, if there is one
+    floor: Option<isize>,
+    // Path of the last grain of sand of every source
+    paths: Vec<Vec<Pos>>,
 }
 
 impl Simulation {
-    fn new(bounds: &Bounds) -> Self {
-        // Generate grid
-        let grid = (0..bounds.height())
+    /// Create a new simulation.
+    /// The floor is placed `floor` rows (at least one) below the bottom of the bounds
+    fn new(bounds: &Bounds, sources: Vec<Pos>, floor: Option<isize>) -> Self {
+        assert!(
+            floor.is_none_or(|depth| depth >= 1),
+            "The floor has to be below the lowest rock"
+        );
+        let floor = floor.map(|depth| bounds.bottom + depth);
+
+        // Generate grid, reaching down to the floor if there is one
+        let height = floor.map_or(bounds.height(), |floor| (floor - bounds.top) as usize);
+        let grid = (0..height)
             .map(|_| [Material::Air].repeat(bounds.width()))
             .collect();
 
         let origin = (bounds.left, bounds.top);
-
-        Self { grid, origin }
+        let paths = vec![vec![]; sources.len()];
+
+        Self {
+            grid,
+            origin,
+            sources,
+            floor,
+            paths,
+        }
     }
 
     /// Draw a line of rocks into the grid
@@ -75,22 +109,93 @@ impl Simulation {
         self.set(line.last().unwrap(), Material::Rock);
     }
 
+    fn width(&self) -> usize {
+        self.grid.first().map_or(0, Vec::len)
+    }
+
+    /// Add columns to the grid until it contains the x-coordinate
+    fn grow(&mut self, x: isize) {
+        if x < self.origin.0 {
+            let n = (self.origin.0 - x) as usize;
+
+            for row in self.grid.iter_mut() {
+                row.splice(0..0, [Material::Air].repeat(n));
+            }
+
+            self.origin.0 = x;
+        } else if x >= self.origin.0 + self.width() as isize {
+            let width = (x - self.origin.0) as usize + 1;
+
+            for row in self.grid.iter_mut() {
+                row.resize(width, Material::Air);
+            }
+        }
+    }
+
     /// Set position in the grid
     fn set(&mut self, pos: &Pos, mat: Material) {
+        self.grow(pos.0);
         self.grid[(pos.1 - self.origin.1) as usize][(pos.0 - self.origin.0) as usize] = mat;
     }
 
+    /// Get material at position.
+    /// With a floor, everything next to the grid is air, otherwise it is the abyss
+    fn get(&self, pos: &Pos) -> Option<Material> {
+        if let Some(floor) = self.floor {
+            if pos.1 == floor {
+                return Some(Material::Rock);
+            }
+        }
+
+        let row = self
+            .grid
+            .get(usize::try_from(pos.1 - self.origin.1).ok()?)?;
+
+        let mat = usize::try_from(pos.0 - self.origin.0)
+            .ok()
+            .and_then(|x| row.get(x))
+            .copied();
+
+        match self.floor {
+            Some(_) => mat.or(Some(Material::Air)),
+            None => mat,
+        }
+    }
+
     /// Check if position is air
     fn is_free(&self, pos: &Pos) -> Option<bool> {
-        self.grid
-            .get(usize::try_from(pos.1 - self.origin.1).ok()?)?
-            .get(usize::try_from(pos.0 - self.origin.0).ok()?)
-            .map(|&mat| mat == Material::Air)
+        self.get(pos).map(|mat| mat == Material::Air)
     }
 
-    /// Simulate sand falling until it can't move anymore or leaves the grid
-    fn simulate_sand(&mut self, pos: &Pos) -> Option<Pos> {
-        let mut pos = *pos;
+    /// Simulate one grain of sand from the given source falling until it can't move anymore
+    /// or leaves the grid.
+    /// Every grain follows the path of the previous one until the previous grain came to rest,
+    /// so the fall resumes from the last position of that path that is still free
+    fn simulate_sand(&mut self, source: usize) -> Option<Pos> {
+        let mut path = std::mem::take(&mut self.paths[source]);
+
+        // Grains of other sources may have filled any cell of the path
+        if self.sources.len() > 1 {
+            if let Some(idx) = path.iter().position(|pos| self.is_free(pos) != Some(true)) {
+                path.truncate(idx);
+            }
+        }
+
+        // Drop positions that have been filled in the meantime
+        while let Some(pos) = path.last() {
+            if self.is_free(pos)? {
+                break;
+            }
+
+            path.pop();
+        }
+
+        // Start at the source if there is no previous path
+        if path.is_empty() {
+            path.push(self.sources[source]);
+        }
+
+        let mut pos = *path.last().unwrap();
 
         loop {
             if self.is_free(&(pos.0, pos.1 + 1))? {
@@ -105,16 +210,142 @@ impl Simulation {
             } else {
                 // Can't move anymore
                 self.set(&pos, Material::Sand);
+                path.pop();
                 break;
             }
+
+            path.push(pos);
         }
 
+        self.paths[source] = path;
+
         Some(pos)
     }
+
+    /// Pour sand from all sources in turn until a grain falls off the grid or every source is blocked.
+    /// Prints the grid after every `frame_every` grains, if set
+    fn pour_sand(&mut self, frame_every: Option<usize>) -> usize {
+        let mut count = 0;
+
+        loop {
+            let active: Vec<usize> = (0..self.sources.len())
+                .filter(|&idx| self.is_free(&self.sources[idx]) == Some(true))
+                .collect();
+
+            if active.is_empty() {
+                break;
+            }
+
+            for source in active {
+                // Source may have been blocked by another source
+                if self.is_free(&self.sources[source]) != Some(true) {
+                    continue;
+                }
+
+                if self.simulate_sand(source).is_none() {
+                    return count;
+                }
+
+                count += 1;
+
+                if frame_every.is_some_and(|n| count % n == 0) {
+                    println!("== After {count} grains ==\n{self}");
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Fill the cave row by row without simulating single grains.
+    /// Sand can reach a cell if it isn't rock and one of the three cells above it holds sand.
+    /// Only possible if the cave has a floor, i.e. no sand can fall off
+    fn fill_rows(&mut self) -> Option<usize> {
+        let floor = self.floor?;
+
+        let mut count = 0;
+
+        // Columns that hold sand in the current row
+        let mut row: Vec<isize> = vec![];
+        let mut y = self.sources.iter().map(|source| source.1).min()?;
+
+        while y < floor {
+            // Add sources in this row
+            for source in &self.sources {
+                if source.1 == y && self.is_free(source) == Some(true) {
+                    row.push(source.0);
+                }
+            }
+            row.sort_unstable();
+            row.dedup();
+
+            for &x in &row {
+                self.set(&(x, y), Material::Sand);
+            }
+            count += row.len();
+
+            // Every cell below or diagonally below a sand cell can be reached
+            let mut next: Vec<isize> = row.iter().flat_map(|&x| [x - 1, x, x + 1]).collect();
+            next.sort_unstable();
+            next.dedup();
+            next.retain(|&x| self.is_free(&(x, y + 1)) == Some(true));
+
+            row = next;
+            y += 1;
+        }
+
+        Some(count)
+    }
+
+    /// All rows of the grid, including the floor
+    fn rows(&self) -> impl Iterator<Item = Vec<Material>> + '_ {
+        let floor = self.floor.map(|_| [Material::Rock].repeat(self.width()));
+
+        self.grid.iter().cloned().chain(floor)
+    }
+
+    /// Export the grid as a binary PPM-image (one pixel per cell)
+    fn to_ppm(&self) -> Vec<u8> {
+        let height = self.rows().count();
+        let width = self.width();
+
+        let mut image = format!("P6\n{width} {height}\n255\n").into_bytes();
+
+        for row in self.rows() {
+            for mat in row {
+                image.extend_from_slice(&mat.color());
+            }
+        }
+
+        image
+    }
 }
 
-/// Parse input into a grid and calculate bounds
-fn parse_lines(input: &str) -> (Vec<Vec<Pos>>, Bounds) {
+impl Display for Simulation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            for (x, mat) in row.iter().enumerate() {
+                let pos = (x as isize + self.origin.0, y as isize + self.origin.1);
+
+                let c = match mat {
+                    Material::Air if self.sources.contains(&pos) => '+',
+                    Material::Air => '.',
+                    Material::Rock => '#',
+                    Material::Sand => 'o',
+                };
+
+                write!(f, "{c}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse input into a grid and calculate bounds, including all sand sources
+fn parse_lines(input: &str, sources: &[Pos]) -> (Vec<Vec<Pos>>, Bounds) {
     let lines: Vec<Vec<Pos>> = input
         .lines()
         .map(|line| {
@@ -129,7 +360,8 @@ fn parse_lines(input: &str) -> (Vec<Vec<Pos>>, Bounds) {
         .collect();
 
     // Find bounds
-    let mut bounds = Bounds::from_pos(&(500, 0));
+    let mut bounds = Bounds::from_pos(&sources[0]);
+    sources.iter().for_each(|pos| bounds.expand(pos));
     lines
         .iter()
         .for_each(|points| points.iter().for_each(|pos| bounds.expand(pos)));
@@ -137,69 +369,164 @@ fn parse_lines(input: &str) -> (Vec<Vec<Pos>>, Bounds) {
     (lines, bounds)
 }
 
-/// Compute the solution to part 1
-fn part_1(input: &str) -> String {
-    let (lines, bounds) = parse_lines(input);
+/// Build the cave with the given sources and an optional floor below the lowest rock
+fn build_cave(input: &str, sources: &[Pos], floor: Option<isize>) -> Simulation {
+    let (lines, bounds) = parse_lines(input, sources);
 
-    let mut sim = Simulation::new(&bounds);
+    let mut sim = Simulation::new(&bounds, sources.to_vec(), floor);
 
     // Draw all lines into simulation
     for line in lines {
         sim.draw_line(&line);
     }
 
+    sim
+}
+
+/// Compute the solution to part 1
+fn part_1(sim: &mut Simulation, frame_every: Option<usize>) -> String {
     // Simulate until sand doesn't rest
-    let mut count = 0;
-    while sim.simulate_sand(&(500, 0)).is_some() {
-        count += 1;
-    }
+    sim.pour_sand(frame_every).to_string()
+}
 
-    count.to_string()
+/// How sand is simulated in part 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Simulate every single grain
+    Grains,
+    /// Fill the cave row by row
+    Rows,
 }
 
 /// Compute the solution to part 2
-fn part_2(input: &str) -> String {
-    let (lines, mut bounds) = parse_lines(input);
-
-    // Expand bottom by 2
-    bounds.bottom += 2;
+fn part_2(sim: &mut Simulation, mode: Mode, frame_every: Option<usize>) -> String {
+    match mode {
+        // Simulate until sand blocks the sources or falls off
+        Mode::Grains => sim.pour_sand(frame_every),
+        Mode::Rows => sim
+            .fill_rows()
+            .expect("Filling row by row requires a floor"),
+    }
+    .to_string()
+}
 
-    // Change bounds to make sure no sand falls off
-    let height = bounds.height();
-    bounds.left = isize::min(bounds.left, 500 - (height as isize));
-    bounds.right = isize::max(bounds.right, 500 + (height as isize));
+fn main() {
+    let input = include_str!("input.txt");
 
-    let mut sim = Simulation::new(&bounds);
+    // Options: --frames <N>, --show, --ppm <PATH>, --mode <grains|rows>,
+    // any number of --source <X,Y> and --floor <DEPTH|none>.
+    // Row fill mode doesn't simulate single grains, so --frames only shows part 1 then
+    let mut frame_every = None;
+    let mut mode = Mode::Grains;
+    let mut show = false;
+    let mut ppm_path = None;
+    let mut sources = vec![];
+    let mut floor = Some(2);
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--frames" => {
+                let n = args.next().expect("--frames requires a number");
+                frame_every = Some(n.parse::<usize>().expect("Invalid frame interval").max(1));
+            }
+            "--show" => show = true,
+            "--mode" => {
+                mode = match args.next().as_deref() {
+                    Some("grains") => Mode::Grains,
+                    Some("rows") => Mode::Rows,
+                    _ => panic!("--mode requires either 'grains' or 'rows'"),
+                }
+            }
+            "--ppm" => ppm_path = Some(args.next().expect("--ppm requires a path")),
+            "--source" => {
+                let source = args.next().expect("--source requires a position");
+                let (x, y) = source.split_once(',').expect("Invalid source");
+                sources.push((x.parse().unwrap(), y.parse().unwrap()));
+            }
+            "--floor" => {
+                floor = match args.next().as_deref() {
+                    Some("none") => None,
+                    Some(depth) => Some(
+                        depth
+                            .parse()
+                            .ok()
+                            .filter(|&depth| depth >= 1)
+                            .expect("--floor requires a depth of at least 1"),
+                    ),
+                    None => panic!("--floor requires a depth or 'none'"),
+                }
+            }
+            _ => panic!("Unknown argument: {arg}"),
+        }
+    }
 
-    // Draw all lines into simulation
-    for line in lines {
-        sim.draw_line(&line);
+    // Default sand source
+    if sources.is_empty() {
+        sources.push((500, 0));
     }
 
-    // Add ground
-    sim.draw_line(&[(bounds.left, bounds.bottom), (bounds.right, bounds.bottom)]);
+    println!("Advent of Code 2022 - Day 14");
+
+    let mut sim_1 = build_cave(input, &sources, None);
+    let part_1 = part_1(&mut sim_1, frame_every);
 
-    // Simulate until sand comes to rest at (500, 0) or falls off
-    let mut count = 0;
-    while let Some(pos) = sim.simulate_sand(&(500, 0)) {
-        count += 1;
+    let mut sim_2 = build_cave(input, &sources, floor);
+    let part_2 = part_2(&mut sim_2, mode, frame_every);
 
-        if pos == (500, 0) {
-            break;
-        }
+    if show {
+        println!("== Part 1 ==\n{sim_1}");
+        println!("== Part 2 ==\n{sim_2}");
+    }
+
+    if let Some(path) = ppm_path {
+        std::fs::write(&path, sim_2.to_ppm()).expect("Could not write image");
     }
 
-    count.to_string()
+    println!("Part 1: {part_1}");
+    println!("Part 2: {part_2}");
 }
 
-fn main() {
-    let input = include_str!("input.txt");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!("Advent of Code 2022 - Day 14");
+    const EXAMPLE: &str = "498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9";
 
-    let part_1 = part_1(input);
-    let part_2 = part_2(input);
+    /// Pouring single grains and filling row by row give the same count and the same cave
+    fn assert_modes_agree(input: &str, sources: &[Pos], floor: isize) {
+        let mut grains = build_cave(input, sources, Some(floor));
+        let mut rows = build_cave(input, sources, Some(floor));
 
-    println!("Part 1: {part_1}");
-    println!("Part 2: {part_2}");
+        assert_eq!(Some(grains.pour_sand(None)), rows.fill_rows());
+        assert_eq!(grains.to_ppm(), rows.to_ppm());
+    }
+
+    #[test]
+    fn grains_and_rows_agree() {
+        for input in [EXAMPLE, include_str!("input.txt")] {
+            for floor in [1, 2, 7] {
+                assert_modes_agree(input, &[(500, 0)], floor);
+            }
+        }
+
+        assert_eq!(
+            part_2(
+                &mut build_cave(EXAMPLE, &[(500, 0)], Some(2)),
+                Mode::Rows,
+                None
+            ),
+            "93"
+        );
+    }
+
+    #[test]
+    fn grains_and_rows_agree_with_sources() {
+        assert_modes_agree(EXAMPLE, &[(500, 0), (490, 2)], 2);
+        assert_modes_agree(
+            include_str!("input.txt"),
+            &[(500, 0), (470, 5), (530, 5)],
+            2,
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::ops::RangeInclusive;
 
 #[derive(Debug)]
 struct Pos {
@@ -43,7 +43,7 @@ impl Sensor {
 
     /// Returns a range that describes the x-values this sensor covers in that row
     /// If the sensor doesn't cover the row at all, None is returned
-    fn covers_row(&self, row: i64) -> Option<std::ops::RangeInclusive<i64>> {
+    fn covers_row(&self, row: i64) -> Option<RangeInclusive<i64>> {
         let Pos { x, y } = self.pos;
         let y_dist = y.abs_diff(row);
 
@@ -55,6 +55,239 @@ impl Sensor {
     }
 }
 
+/// A sorted list of disjoint, non-adjacent integer intervals
+#[derive(Debug, Default, Clone)]
+struct IntervalSet {
+    intervals: Vec<RangeInclusive<i64>>,
+}
+
+impl IntervalSet {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a range, merging it with all overlapping or adjacent intervals
+    fn insert(&mut self, range: RangeInclusive<i64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let (mut start, mut end) = range.into_inner();
+
+        // First interval that could touch the new range
+        let first = self
+            .intervals
+            .partition_point(|interval| interval.end().saturating_add(1) < start);
+
+        // Interval after the last one that touches the new range
+        let last = self
+            .intervals
+            .partition_point(|interval| *interval.start() <= end.saturating_add(1));
+
+        // Merge all touching intervals into the new one
+        if first < last {
+            start = start.min(*self.intervals[first].start());
+            end = end.max(*self.intervals[last - 1].end());
+        }
+
+        self.intervals.splice(first..last, [start..=end]);
+    }
+
+    /// Check if the value is contained in any interval
+    fn contains(&self, value: i64) -> bool {
+        let idx = self
+            .intervals
+            .partition_point(|interval| *interval.end() < value);
+
+        self.intervals
+            .get(idx)
+            .is_some_and(|interval| interval.contains(&value))
+    }
+
+    /// All ranges inside the bounds that are not contained in the set
+    fn gaps(&self, bounds: RangeInclusive<i64>) -> Vec<RangeInclusive<i64>> {
+        let mut gaps = vec![];
+        let mut next = *bounds.start();
+
+        for interval in &self.intervals {
+            if *interval.start() > *bounds.end() {
+                break;
+            }
+
+            if *interval.start() > next {
+                gaps.push(next..=interval.start() - 1);
+            }
+
+            next = next.max(interval.end().saturating_add(1));
+        }
+
+        if next <= *bounds.end() {
+            gaps.push(next..=*bounds.end());
+        }
+
+        gaps
+    }
+
+    /// Number of integers contained in the set
+    fn len(&self) -> u64 {
+        self.intervals
+            .iter()
+            .map(|interval| interval.end().abs_diff(*interval.start()) + 1)
+            .sum()
+    }
+}
+
+impl FromIterator<RangeInclusive<i64>> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = RangeInclusive<i64>>>(iter: T) -> Self {
+        let mut set = IntervalSet::new();
+        iter.into_iter().for_each(|range| set.insert(range));
+        set
+    }
+}
+
+/// Rectangular area, all bounds are inclusive
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rect {
+    left: i64,
+    top: i64,
+    right: i64,
+    bottom: i64,
+}
+
+impl Rect {
+    fn width(&self) -> i64 {
+        self.right - self.left + 1
+    }
+
+    fn height(&self) -> i64 {
+        self.bottom - self.top + 1
+    }
+
+    fn contains(&self, pos: &Pos) -> bool {
+        (self.left..=self.right).contains(&pos.x) && (self.top..=self.bottom).contains(&pos.y)
+    }
+
+    /// All positions inside the rectangle
+    fn cells(&self) -> impl Iterator<Item = Pos> + '_ {
+        (self.top..=self.bottom).flat_map(|y| (self.left..=self.right).map(move |x| Pos::new(x, y)))
+    }
+}
+
+/// Merge the coverage of all sensors in the given row
+fn row_coverage(sensors: &[Sensor], row: i64) -> IntervalSet {
+    sensors
+        .iter()
+        .filter_map(|sensor| sensor.covers_row(row))
+        .collect()
+}
+
+/// Find all areas inside the search area that are not covered by any sensor.
+/// Uncovered ranges in consecutive rows with the same x-range are merged into one rectangle
+fn uncovered_regions(sensors: &[Sensor], area: &Rect) -> Vec<Rect> {
+    let mut closed = vec![];
+    let mut open: Vec<Rect> = vec![];
+
+    for y in area.top..=area.bottom {
+        let gaps = row_coverage(sensors, y).gaps(area.left..=area.right);
+
+        // Close rectangles that don't continue in this row
+        let (continued, ended): (Vec<_>, Vec<_>) = open.into_iter().partition(|rect| {
+            gaps.iter()
+                .any(|gap| (*gap.start(), *gap.end()) == (rect.left, rect.right))
+        });
+        closed.extend(ended);
+        open = continued;
+
+        for gap in gaps {
+            if let Some(rect) = open.iter_mut().find(|rect| rect.left == *gap.start()) {
+                rect.bottom = y;
+            } else {
+                open.push(Rect {
+                    left: *gap.start(),
+                    top: y,
+                    right: *gap.end(),
+                    bottom: y,
+                });
+            }
+        }
+    }
+
+    closed.extend(open);
+    closed
+}
+
+/// Every single position inside the search area that is not covered by any sensor
+fn uncovered_cells(sensors: &[Sensor], area: &Rect) -> Vec<Pos> {
+    uncovered_regions(sensors, area)
+        .iter()
+        .flat_map(Rect::cells)
+        .collect()
+}
+
+/// Render a scaled-down map of the area with `cols` x `rows` characters.
+/// Every character represents a block of positions:
+/// 'S' and 'B' mark sensors and beacons, '!' marks uncovered positions,
+/// '#' and '.' show if the center of the block is covered.
+/// Blocks completely outside of the area (if it is smaller than the map) are left empty
+fn render(sensors: &[Sensor], area: &Rect, cols: i64, rows: i64) -> String {
+    assert!(cols > 0 && rows > 0, "The map needs at least one character");
+
+    let uncovered = uncovered_regions(sensors, area);
+
+    // Size of a block, rounded up to fit the whole area
+    let block_width = (area.width() + cols - 1) / cols;
+    let block_height = (area.height() + rows - 1) / rows;
+
+    let mut output = String::new();
+
+    for row in 0..rows {
+        for col in 0..cols {
+            // The last blocks are clipped to the area
+            let block = Rect {
+                left: area.left + col * block_width,
+                top: area.top + row * block_height,
+                right: i64::min(area.left + (col + 1) * block_width - 1, area.right),
+                bottom: i64::min(area.top + (row + 1) * block_height - 1, area.bottom),
+            };
+
+            if block.left > block.right || block.top > block.bottom {
+                output.push(' ');
+                continue;
+            }
+
+            let center = Pos::new(
+                (block.left + block.right) / 2,
+                (block.top + block.bottom) / 2,
+            );
+
+            let overlaps = |rect: &Rect| {
+                rect.left <= block.right
+                    && block.left <= rect.right
+                    && rect.top <= block.bottom
+                    && block.top <= rect.bottom
+            };
+
+            let c = if sensors.iter().any(|sensor| block.contains(&sensor.pos)) {
+                'S'
+            } else if sensors.iter().any(|sensor| block.contains(&sensor.beacon)) {
+                'B'
+            } else if uncovered.iter().any(overlaps) {
+                '!'
+            } else if sensors.iter().any(|sensor| sensor.covers(&center)) {
+                '#'
+            } else {
+                '.'
+            };
+
+            output.push(c);
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
 /// Parse input, nothing special
 fn parse_input(input: &str) -> Vec<Sensor> {
     input
@@ -77,38 +310,34 @@ fn parse_input(input: &str) -> Vec<Sensor> {
 fn part_1(row: i64, input: &str) -> String {
     let sensors = parse_input(input);
 
-    // Use a hashset to keep track of positions. Probably not the fastest but it works
-    let mut covered = HashSet::new();
-
-    // For every sensor add the covered positions in the row to a hashset
-    for sensor in &sensors {
-        if let Some(range) = sensor.covers_row(row) {
-            for x in range {
-                covered.insert(x);
-            }
-        }
-    }
+    // Merge the ranges of all sensors into disjoint intervals
+    let covered = row_coverage(&sensors, row);
 
-    // Remove beacons from the covered set
-    // This was missing in my solution before but the answer was right by coincidence...
-    for sensor in &sensors {
-        if sensor.beacon.y == row {
-            covered.remove(&sensor.beacon.x);
-        }
-    }
+    // Beacons in the row can't be counted, every one of them lies inside the coverage
+    let mut beacons: Vec<i64> = sensors
+        .iter()
+        .filter(|sensor| sensor.beacon.y == row)
+        .map(|sensor| sensor.beacon.x)
+        .filter(|&x| covered.contains(x))
+        .collect();
+    beacons.sort_unstable();
+    beacons.dedup();
 
-    // Length of the set is the answer
-    covered.len().to_string()
+    (covered.len() - beacons.len() as u64).to_string()
 }
 
-/// Compute the solution to part 2
-fn part_2(range: i64, input: &str) -> String {
-    let sensors = parse_input(input);
-
-    let mut result = 0;
+/// Strategy to find the distress beacon in part 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    /// Scan every row, skipping covered ranges
+    Scan,
+    /// Only check intersections of the lines just outside the sensor diamonds
+    Boundaries,
+}
 
-    // Check every position by scanning from left to right and top to bottom
-    'outer: for y in 0..=range {
+/// Check every position by scanning from left to right and top to bottom
+fn find_beacon_scan(range: i64, sensors: &[Sensor]) -> Option<Pos> {
+    for y in 0..=range {
         let mut x = 0;
         while x <= range {
             // For each sensor:
@@ -128,23 +357,267 @@ fn part_2(range: i64, input: &str) -> String {
                 x = new_x + 1;
             } else {
                 // Otherwise we have found an uncovered position and can stop
-                result = x * 4000000 + y;
-                break 'outer;
+                return Some(Pos::new(x, y));
             }
         }
     }
 
-    result.to_string()
+    None
+}
+
+/// Find the beacon using rotated coordinates u = x + y and v = x - y.
+/// In those coordinates every sensor covers a square, so the uncovered position has to lie on an
+/// intersection of the lines just outside the squares (or on the border of the search area)
+fn find_beacon_boundaries(range: i64, sensors: &[Sensor]) -> Option<Pos> {
+    // Lines just outside of every sensor, u = x + y is constant on an "ascending" line
+    let mut u_lines = vec![0, range, 2 * range];
+    let mut v_lines = vec![-range, 0, range];
+
+    for sensor in sensors {
+        let Pos { x, y } = sensor.pos;
+        let r = sensor.radius as i64 + 1;
+
+        u_lines.extend([x + y - r, x + y + r]);
+        v_lines.extend([x - y - r, x - y + r]);
+    }
+
+    u_lines.sort_unstable();
+    u_lines.dedup();
+    v_lines.sort_unstable();
+    v_lines.dedup();
+
+    // Borders of the search area
+    let borders = [0, range];
+
+    // Intersections of lines with each other and with the borders
+    let intersections = u_lines.iter().flat_map(|&u| {
+        let with_v = v_lines
+            .iter()
+            // Only integer positions
+            .filter(move |&&v| (u - v) % 2 == 0)
+            .map(move |&v| Pos::new((u + v) / 2, (u - v) / 2));
+
+        let with_borders = borders
+            .into_iter()
+            .flat_map(move |b| [Pos::new(b, u - b), Pos::new(u - b, b)]);
+
+        with_v.chain(with_borders)
+    });
+
+    let v_borders = v_lines.iter().flat_map(|&v| {
+        borders
+            .into_iter()
+            .flat_map(move |b| [Pos::new(b, b - v), Pos::new(v + b, b)])
+    });
+
+    intersections
+        .chain(v_borders)
+        .filter(|pos| (0..=range).contains(&pos.x) && (0..=range).contains(&pos.y))
+        .find(|pos| sensors.iter().all(|sensor| !sensor.covers(pos)))
+}
+
+/// Compute the solution to part 2
+fn part_2(range: i64, input: &str, strategy: Strategy) -> String {
+    let sensors = parse_input(input);
+
+    let beacon = match strategy {
+        Strategy::Scan => find_beacon_scan(range, &sensors),
+        Strategy::Boundaries => find_beacon_boundaries(range, &sensors),
+    };
+
+    // Tuning frequency of the beacon
+    beacon.map_or(0, |Pos { x, y }| x * 4000000 + y).to_string()
 }
 
 fn main() {
     let input = include_str!("input.txt");
 
+    // Options: --strategy <scan|boundaries>, --area <X0,Y0,X1,Y1>,
+    // --uncovered, --uncovered-cells and --render <COLS,ROWS>
+    let mut strategy = Strategy::Scan;
+    let mut area = Rect {
+        left: 0,
+        top: 0,
+        right: 4000000,
+        bottom: 4000000,
+    };
+    let mut show_uncovered = false;
+    let mut show_cells = false;
+    let mut render_size = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--strategy" => {
+                strategy = match args.next().as_deref() {
+                    Some("scan") => Strategy::Scan,
+                    Some("boundaries") => Strategy::Boundaries,
+                    _ => panic!("--strategy requires either 'scan' or 'boundaries'"),
+                }
+            }
+            "--area" => {
+                let values: Vec<i64> = args
+                    .next()
+                    .expect("--area requires a rectangle")
+                    .split(',')
+                    .map(|v| v.parse().expect("Invalid area"))
+                    .collect();
+
+                let [left, top, right, bottom] = values[..] else {
+                    panic!("--area requires four values");
+                };
+                area = Rect {
+                    left,
+                    top,
+                    right,
+                    bottom,
+                };
+            }
+            "--uncovered" => show_uncovered = true,
+            "--uncovered-cells" => show_cells = true,
+            "--render" => {
+                let size = args.next().expect("--render requires a size");
+                let (cols, rows) = size.split_once(',').expect("Invalid size");
+                let (cols, rows): (i64, i64) = (cols.parse().unwrap(), rows.parse().unwrap());
+                assert!(cols > 0 && rows > 0, "--render requires a positive size");
+                render_size = Some((cols, rows));
+            }
+            _ => panic!("Unknown argument: {arg}"),
+        }
+    }
+
     println!("Advent of Code 2022 - Day 15");
 
+    if show_uncovered || show_cells || render_size.is_some() {
+        let sensors = parse_input(input);
+
+        if let Some((cols, rows)) = render_size {
+            print!("{}", render(&sensors, &area, cols, rows));
+        }
+
+        if show_uncovered {
+            for rect in uncovered_regions(&sensors, &area) {
+                println!(
+                    "Uncovered: x={}..={}, y={}..={} ({} positions)",
+                    rect.left,
+                    rect.right,
+                    rect.top,
+                    rect.bottom,
+                    rect.width() * rect.height()
+                );
+            }
+        }
+
+        if show_cells {
+            for Pos { x, y } in uncovered_cells(&sensors, &area) {
+                println!("Uncovered: x={x}, y={y}");
+            }
+        }
+    }
+
     let part_1_res = part_1(2000000, input);
     println!("Part 1: {part_1_res}");
 
-    let part_2_res = part_2(4000000, input);
+    let part_2_res = part_2(4000000, input, strategy);
     println!("Part 2: {part_2_res}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3";
+
+    #[test]
+    fn strategies_agree() {
+        for strategy in [Strategy::Scan, Strategy::Boundaries] {
+            assert_eq!(part_2(20, EXAMPLE, strategy), "56000011");
+        }
+
+        let input = include_str!("input.txt");
+        assert_eq!(
+            part_2(4000000, input, Strategy::Scan),
+            part_2(4000000, input, Strategy::Boundaries)
+        );
+    }
+
+    /// Sensors and beacons outside of the area are not drawn, even if the last block is larger
+    #[test]
+    fn render_clips_to_area() {
+        let sensors = vec![
+            Sensor::new(Pos::new(2, 2), Pos::new(2, 3)),
+            Sensor::new(Pos::new(10, 10), Pos::new(11, 10)),
+        ];
+        let area = Rect {
+            left: 0,
+            top: 0,
+            right: 9,
+            bottom: 9,
+        };
+
+        let map = render(&sensors, &area, 3, 3);
+
+        assert_eq!(map.matches('S').count(), 1);
+        assert_eq!(map.matches('B').count(), 0);
+        assert_eq!(render(&sensors, &area, 20, 1).matches(' ').count(), 10);
+    }
+
+    /// Generated sensors with exactly one uncovered position, both strategies have to find it
+    #[test]
+    fn strategies_agree_on_generated_inputs() {
+        let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+        let mut random = |range: i64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % (2 * range as u64 + 1)) as i64 - range
+        };
+
+        let area = Rect {
+            left: 0,
+            top: 0,
+            right: 20,
+            bottom: 20,
+        };
+        let mut checked = 0;
+
+        for _ in 0..5000 {
+            let sensors: Vec<Sensor> = (0..8)
+                .map(|_| {
+                    let pos = Pos::new(10 + random(14), 10 + random(14));
+                    let beacon = Pos::new(pos.x + random(9), pos.y + random(9));
+                    Sensor::new(pos, beacon)
+                })
+                .collect();
+
+            let uncovered = uncovered_cells(&sensors, &area);
+            let expected = match &uncovered[..] {
+                [] => None,
+                [pos] => Some((pos.x, pos.y)),
+                _ => continue,
+            };
+
+            for find in [find_beacon_scan, find_beacon_boundaries] {
+                let found = find(20, &sensors).map(|pos| (pos.x, pos.y));
+                assert_eq!(found, expected, "{sensors:?}");
+            }
+
+            checked += expected.is_some() as usize;
+        }
+
+        assert!(checked > 0);
+    }
+}
@@ -1,6 +1,4 @@
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-
-use rayon::prelude::*;
+use std::{collections::HashMap, fmt::Display};
 
 #[derive(Debug, Clone)]
 struct Valve<'a> {
@@ -9,7 +7,7 @@ struct Valve<'a> {
     tunnels: Vec<&'a str>,
 }
 
-fn parse_line(line: &str) -> Option<Valve> {
+fn parse_line(line: &str) -> Option<Valve<'_>> {
     let line = line.strip_prefix("Valve ")?;
     let name = &line[..2];
     let line = &line[2..].strip_prefix(" has flow rate=")?;
@@ -25,39 +23,104 @@ fn parse_line(line: &str) -> Option<Valve> {
     })
 }
 
-fn parse_input(input: &str) -> Vec<Valve> {
+fn parse_input(input: &str) -> Vec<Valve<'_>> {
     input.lines().filter_map(parse_line).collect()
 }
 
-fn key<'a>(left: &'a str, right: &'a str) -> (&'a str, &'a str) {
-    if left < right {
-        (left, right)
-    } else {
-        (right, left)
+/// Valves interned to indices, with a dense matrix of the shortest distances between them
+struct Network<'a> {
+    valves: Vec<Valve<'a>>,
+    distances: Vec<Vec<u32>>,
+    // Bitmask of every valve, only valves with a flow rate get a bit
+    masks: Vec<u64>,
+    start: usize,
+}
+
+impl<'a> Network<'a> {
+    fn new(valves: Vec<Valve<'a>>, start: &str) -> Self {
+        let distances = floyd(&valves);
+
+        // Assign bits to all valves with a flow rate
+        let mut next_bit = 0;
+        let masks = valves
+            .iter()
+            .map(|valve| {
+                if valve.flow_rate == 0 {
+                    return 0;
+                }
+
+                let mask = 1 << next_bit;
+                next_bit += 1;
+                mask
+            })
+            .collect();
+
+        // The opened valves are stored in a u64
+        assert!(
+            next_bit <= 64,
+            "Only up to 64 valves with a flow rate are supported"
+        );
+
+        let start = valves.iter().position(|v| v.name == start).unwrap();
+
+        Self {
+            valves,
+            distances,
+            masks,
+            start,
+        }
+    }
+
+    /// Shortest path between two valves, excluding the start
+    fn path(&self, mut from: usize, to: usize) -> Vec<&'a str> {
+        let mut path = vec![];
+
+        while from != to {
+            // Step to a neighbor that is one step closer
+            from = (0..self.valves.len())
+                .find(|&n| {
+                    self.distances[from][n] == 1
+                        && self.distances[n][to] + 1 == self.distances[from][to]
+                })
+                .unwrap();
+
+            path.push(self.valves[from].name);
+        }
+
+        path
+    }
+
+    /// Indices of all valves with a flow rate
+    fn useful(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.valves.len()).filter(|&idx| self.masks[idx] != 0)
     }
 }
 
-fn floyd<'a>(valves: &Vec<Valve<'a>>) -> HashMap<(&'a str, &'a str), u32> {
-    let mut graph: HashMap<(&str, &str), u32> = HashMap::new();
+/// Calculate the shortest distances between all valves
+fn floyd(valves: &[Valve]) -> Vec<Vec<u32>> {
+    let indices: HashMap<&str, usize> = valves
+        .iter()
+        .enumerate()
+        .map(|(idx, valve)| (valve.name, idx))
+        .collect();
 
-    for i in valves {
-        for j in &i.tunnels {
-            graph.insert(key(i.name, j), 1);
+    let n = valves.len();
+    let mut graph = vec![vec![u32::MAX; n]; n];
+
+    for (i, valve) in valves.iter().enumerate() {
+        graph[i][i] = 0;
+
+        for j in &valve.tunnels {
+            graph[i][indices[j]] = 1;
         }
     }
 
-    for Valve { name: k, .. } in valves {
-        for Valve { name: i, .. } in valves {
-            for Valve { name: j, .. } in valves {
-                let min = u32::min(
-                    *graph.get(&key(i, j)).unwrap_or(&u32::MAX),
-                    graph
-                        .get(&key(i, k))
-                        .unwrap_or(&u32::MAX)
-                        .saturating_add(*graph.get(&key(k, j)).unwrap_or(&u32::MAX)),
-                );
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
+                let min = u32::min(graph[i][j], graph[i][k].saturating_add(graph[k][j]));
 
-                graph.insert(key(i, j), min);
+                graph[i][j] = min;
             }
         }
     }
@@ -65,146 +128,442 @@ fn floyd<'a>(valves: &Vec<Valve<'a>>) -> HashMap<(&'a str, &'a str), u32> {
     graph
 }
 
+/// Highest pressure that can still be released, starting at `pos` with `time` minutes left.
+/// Memoised over (position, time, opened valves)
+fn max_pressure(
+    network: &Network,
+    pos: usize,
+    time: u32,
+    opened: u64,
+    cache: &mut HashMap<(usize, u32, u64), u32>,
+) -> u32 {
+    if let Some(&flow) = cache.get(&(pos, time, opened)) {
+        return flow;
+    }
+
+    let flow = network
+        .useful()
+        .filter(|&v| opened & network.masks[v] == 0)
+        // Move to the valve and open it
+        .filter(|&v| network.distances[pos][v] < time)
+        .map(|v| {
+            let time = time - network.distances[pos][v] - 1;
+
+            network.valves[v].flow_rate * time
+                + max_pressure(network, v, time, opened | network.masks[v], cache)
+        })
+        .max()
+        .unwrap_or(0);
+
+    cache.insert((pos, time, opened), flow);
+
+    flow
+}
+
 /// Compute the solution to part 1
 fn part_1(input: &str) -> String {
-    let valves = parse_input(input);
-
-    let graph = floyd(&valves);
-
-    fn traverse(
-        mut time: u32,
-        pos: &str,
-        valves: &Vec<Valve>,
-        opened: &HashSet<&str>,
-        graph: &HashMap<(&str, &str), u32>,
-    ) -> u32 {
-        if time == 0 {
-            return 0;
+    let network = Network::new(parse_input(input), "AA");
+
+    let res = max_pressure(&network, network.start, 30, 0, &mut HashMap::new());
+
+    res.to_string()
+}
+
+/// Largest number of valves with a flow rate for the table of all subsets (2^n entries)
+const MAX_SUBSET_VALVES: usize = 24;
+
+/// Highest pressure a single agent can release for every set of opened valves,
+/// together with the order in which the valves are opened.
+/// The result is indexed by the bitmask of opened valves
+fn pressure_per_subset(network: &Network, time: u32) -> (Vec<u32>, Vec<Vec<usize>>) {
+    fn explore(
+        network: &Network,
+        pos: usize,
+        time: u32,
+        opened: u64,
+        pressure: u32,
+        order: &mut Vec<usize>,
+        best: &mut [(u32, Vec<usize>)],
+    ) {
+        let entry = &mut best[opened as usize];
+        if pressure > entry.0 {
+            *entry = (pressure, order.clone());
         }
 
-        let valve = valves.iter().find(|v| v.name == pos).unwrap();
-        let mut flow = 0;
-        let mut opened = opened.clone();
+        for v in network.useful() {
+            if opened & network.masks[v] != 0 || network.distances[pos][v] >= time {
+                continue;
+            }
+
+            // Move to the valve and open it
+            let time = time - network.distances[pos][v] - 1;
+            let pressure = pressure + network.valves[v].flow_rate * time;
+
+            order.push(v);
+            explore(
+                network,
+                v,
+                time,
+                opened | network.masks[v],
+                pressure,
+                order,
+                best,
+            );
+            order.pop();
+        }
+    }
+
+    let count = network.useful().count();
+    assert!(
+        count <= MAX_SUBSET_VALVES,
+        "Only up to {MAX_SUBSET_VALVES} valves with a flow rate are supported for multiple agents"
+    );
+
+    let mut best = vec![(0, vec![]); 1 << count];
+    explore(network, network.start, time, 0, 0, &mut vec![], &mut best);
+
+    best.into_iter().unzip()
+}
+
+/// Highest pressure that can be released by multiple agents working together.
+/// Every agent opens a disjoint set of valves, so the best result for every subset is combined
+/// with the best result of the other agents on the remaining valves.
+/// Returns the pressure and the order in which every agent opens its valves
+fn max_pressure_agents(network: &Network, agents: usize, time: u32) -> (u32, Vec<Vec<usize>>) {
+    // Without agents, no valve is opened
+    if agents == 0 {
+        return (0, vec![]);
+    }
 
-        if valve.flow_rate > 0 {
-            time -= 1;
-            flow = valve.flow_rate * time;
-            opened.insert(pos);
+    let (mut best, orders) = pressure_per_subset(network, time);
+    let all = best.len() - 1;
+
+    // Subset that actually reaches the best pressure of every entry
+    let mut exact: Vec<usize> = (0..=all).collect();
+
+    // Change the table to contain the best pressure for opening at most the given valves
+    for bit in (0..).map(|b| 1 << b).take_while(|&bit| bit <= all) {
+        for mask in 0..=all {
+            if mask & bit != 0 && best[mask ^ bit] > best[mask] {
+                best[mask] = best[mask ^ bit];
+                exact[mask] = exact[mask ^ bit];
+            }
         }
+    }
+
+    // Add one agent after another, each time combining all disjoint subsets.
+    // Remember the subset of the new agent for every combination
+    let mut combined = best.clone();
+    let mut choices = vec![];
+    for _ in 1..agents {
+        let (next, choice): (Vec<_>, Vec<_>) = (0..=all)
+            .map(|mask| {
+                // Iterate over all subsets of mask
+                let mut sub = mask;
+                let mut max = (0, 0);
+
+                loop {
+                    let pressure = best[sub] + combined[mask ^ sub];
+                    if pressure >= max.0 {
+                        max = (pressure, sub);
+                    }
+
+                    if sub == 0 {
+                        break;
+                    }
+                    sub = (sub - 1) & mask;
+                }
+
+                max
+            })
+            .unzip();
+
+        combined = next;
+        choices.push(choice);
+    }
+
+    // Walk back through the choices to find the valves of every agent
+    let mut remaining = all;
+    let mut agent_orders = vec![];
+    for choice in choices.iter().rev() {
+        let sub = choice[remaining];
+        agent_orders.push(orders[exact[sub]].clone());
+        remaining ^= sub;
+    }
+    agent_orders.push(orders[exact[remaining]].clone());
 
-        let sub_flow = valves
+    (combined[all], agent_orders)
+}
+
+/// A valve opened by an agent
+#[derive(Debug)]
+struct Opening<'a> {
+    /// Minute in which the valve is opened
+    minute: u32,
+    valve: &'a str,
+    flow_rate: u32,
+    /// Total pressure released by this valve until the time runs out
+    pressure: u32,
+    /// Valves the agent moves through to get here, including this one
+    path: Vec<&'a str>,
+}
+
+/// Plan of valve openings for every agent
+#[derive(Debug)]
+struct Schedule<'a> {
+    time: u32,
+    agents: Vec<Vec<Opening<'a>>>,
+}
+
+impl<'a> Schedule<'a> {
+    /// Create the schedule from the order in which every agent opens its valves
+    fn new(network: &Network<'a>, time: u32, orders: &[Vec<usize>]) -> Self {
+        let agents = orders
             .iter()
-            .filter(|v| v.flow_rate > 0 && !opened.contains(v.name))
-            .filter(|v| graph[&key(pos, v.name)] < time)
-            .map(|v| {
-                traverse(
-                    time - graph[&key(pos, v.name)],
-                    v.name,
-                    valves,
-                    &opened,
-                    graph,
-                )
+            .map(|order| {
+                let mut pos = network.start;
+                let mut minute = 0;
+
+                order
+                    .iter()
+                    .map(|&v| {
+                        let path = network.path(pos, v);
+
+                        minute += network.distances[pos][v] + 1;
+                        pos = v;
+
+                        let flow_rate = network.valves[v].flow_rate;
+
+                        Opening {
+                            minute,
+                            valve: network.valves[v].name,
+                            flow_rate,
+                            pressure: flow_rate * (time - minute),
+                            path,
+                        }
+                    })
+                    .collect()
             })
-            .max()
-            .unwrap_or(0);
+            .collect();
 
-        flow + sub_flow
+        Self { time, agents }
     }
 
-    let res = traverse(30, "AA", &valves, &HashSet::new(), &graph);
+    fn pressure(&self) -> u32 {
+        self.agents.iter().flatten().map(|o| o.pressure).sum()
+    }
+}
 
-    res.to_string()
+/// What an agent does in a single minute
+#[derive(Debug, Clone, Copy)]
+enum Action<'a> {
+    Move(&'a str),
+    Open(&'a str),
 }
 
-/// Compute the solution to part 2
-fn part_2(input: &str) -> String {
-    let valves = parse_input(input);
-
-    let graph = floyd(&valves);
-
-    fn traverse(
-        mut time: u32,
-        mut me: (&str, u32),
-        mut el: (&str, u32),
-        valves: &Vec<Valve>,
-        opened: &HashSet<&str>,
-        graph: &HashMap<(&str, &str), u32>,
-    ) -> u32 {
-        if time == 0 {
-            return 0;
-        }
+/// Name of the agent for the narrative
+fn agent_name(agent: usize) -> String {
+    match agent {
+        0 => "You".to_string(),
+        1 => "The elephant".to_string(),
+        n => format!("Elephant {n}"),
+    }
+}
 
-        if opened.len() == valves.iter().filter(|v| v.flow_rate > 0).count() {
-            return 0;
-        }
+/// Print the schedule in the minute-by-minute style of the puzzle
+impl Display for Schedule<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Action of every agent in every minute
+        let actions: Vec<Vec<Option<Action>>> = self
+            .agents
+            .iter()
+            .map(|openings| {
+                let mut actions = vec![None; self.time as usize];
+                let mut minute = 0;
 
-        time -= 1;
+                for opening in openings {
+                    for valve in &opening.path {
+                        actions[minute] = Some(Action::Move(valve));
+                        minute += 1;
+                    }
 
-        let valve_me = valves.iter().find(|v| v.name == me.0).unwrap();
-        let valve_el = valves.iter().find(|v| v.name == el.0).unwrap();
+                    actions[minute] = Some(Action::Open(opening.valve));
+                    minute += 1;
+                }
 
-        me.1 -= 1;
-        el.1 -= 1;
+                actions
+            })
+            .collect();
 
-        let mut flow = 0;
-        let mut opened = opened.clone();
+        for minute in 1..=self.time {
+            writeln!(f, "== Minute {minute} ==")?;
 
-        if valve_me.flow_rate > 0 && !opened.contains(&me.0) && me.1 == 0 {
-            flow += valve_me.flow_rate * time;
-            me.1 += 1;
-            opened.insert(me.0);
+            // Valves opened in previous minutes
+            let mut open: Vec<&Opening> = self
+                .agents
+                .iter()
+                .flatten()
+                .filter(|o| o.minute < minute)
+                .collect();
+            open.sort_by_key(|o| o.valve);
+
+            let names: Vec<&str> = open.iter().map(|o| o.valve).collect();
+            let releasing: u32 = open.iter().map(|o| o.flow_rate).sum();
+
+            match names[..] {
+                [] => writeln!(f, "No valves are open.")?,
+                [valve] => writeln!(f, "Valve {valve} is open, releasing {releasing} pressure.")?,
+                [ref rest @ .., last] => writeln!(
+                    f,
+                    "Valves {}{} and {last} are open, releasing {releasing} pressure.",
+                    rest.join(", "),
+                    if rest.len() > 1 { "," } else { "" },
+                )?,
+            }
+
+            for (agent, actions) in actions.iter().enumerate() {
+                let name = agent_name(agent);
+                // Everyone except "You" needs the third person
+                let s = if agent == 0 { "" } else { "s" };
+
+                match actions[minute as usize - 1] {
+                    Some(Action::Move(valve)) => writeln!(f, "{name} move{s} to valve {valve}.")?,
+                    Some(Action::Open(valve)) => writeln!(f, "{name} open{s} valve {valve}.")?,
+                    None => {}
+                }
+            }
+
+            writeln!(f)?;
         }
 
-        if valve_el.flow_rate > 0 && !opened.contains(&el.0) && el.1 == 0 {
-            flow += valve_el.flow_rate * time;
-            el.1 += 1;
-            opened.insert(el.0);
+        for (agent, openings) in self.agents.iter().enumerate() {
+            writeln!(f, "== {} ==", agent_name(agent))?;
+
+            for opening in openings {
+                writeln!(
+                    f,
+                    "Minute {:>2}: open {} ({} x {} = {})",
+                    opening.minute,
+                    opening.valve,
+                    opening.flow_rate,
+                    self.time - opening.minute,
+                    opening.pressure
+                )?;
+            }
         }
 
-        let unopened_valves: Vec<_> = valves
-            .iter()
-            .filter(|v| !opened.contains(v.name))
-            .filter(|v| v.flow_rate > 0)
-            .filter(|v| v.name != me.0 && v.name != el.0)
-            .map(|v| v.name)
-            .collect();
+        write!(f, "Total pressure released: {}", self.pressure())
+    }
+}
 
-        let subflow = match (me.1, el.1) {
-            (0, 0) => unopened_valves
-                .iter()
-                .enumerate()
-                .flat_map(|(idx, v1)| unopened_valves[idx + 1..].iter().map(move |v2| (v1, v2)))
-                .collect::<Vec<_>>()
-                .par_iter()
-                .map(|(me_n, el_n)| {
-                    traverse(
-                        time,
-                        (me_n, graph[&key(me.0, me_n)]),
-                        (el_n, graph[&key(el.0, el_n)]),
-                        valves,
-                        &opened,
-                        graph,
-                    )
-                })
-                .max()
-                .unwrap_or(0),
-            (0, _) => unopened_valves
-                .par_iter()
-                .map(|v| traverse(time, (v, graph[&key(me.0, v)]), el, valves, &opened, graph))
-                .max()
-                .unwrap_or(0),
-            (_, 0) => unopened_valves
-                .par_iter()
-                .map(|v| traverse(time, me, (v, graph[&key(el.0, v)]), valves, &opened, graph))
-                .max()
-                .unwrap_or(0),
-            _ => traverse(time, me, el, valves, &opened, graph),
+/// Colors used to highlight the route of every agent
+const ROUTE_COLORS: [&str; 5] = ["red", "blue", "darkgreen", "orange", "purple"];
+
+/// Edges travelled by every agent, either tunnel by tunnel or from valve to valve
+fn route_edges<'a>(
+    network: &Network<'a>,
+    schedule: &Schedule<'a>,
+    compressed: bool,
+) -> HashMap<(&'a str, &'a str), usize> {
+    let start = network.valves[network.start].name;
+    let mut edges = HashMap::new();
+
+    for (agent, openings) in schedule.agents.iter().enumerate() {
+        let mut prev = start;
+
+        for opening in openings {
+            let steps = if compressed {
+                vec![opening.valve]
+            } else {
+                opening.path.clone()
+            };
+
+            for valve in steps {
+                edges.insert(key(prev, valve), agent);
+                prev = valve;
+            }
+        }
+    }
+
+    edges
+}
+
+/// Order names to use them as key for undirected edges
+fn key<'a>(left: &'a str, right: &'a str) -> (&'a str, &'a str) {
+    if left < right {
+        (left, right)
+    } else {
+        (right, left)
+    }
+}
+
+/// Export the graph to DOT-format, optionally highlighting the route of a schedule.
+/// The compressed graph only contains valves with a flow rate and the start,
+/// connected by edges weighted with their distance
+fn to_dot(network: &Network, route: Option<&Schedule>, compressed: bool) -> String {
+    let route = route
+        .map(|schedule| route_edges(network, schedule, compressed))
+        .unwrap_or_default();
+
+    let valves: Vec<usize> = (0..network.valves.len())
+        .filter(|&v| !compressed || v == network.start || network.masks[v] != 0)
+        .collect();
+
+    let mut dot = String::from("graph valves {\n");
+
+    for &v in &valves {
+        let Valve {
+            name, flow_rate, ..
+        } = network.valves[v];
+
+        let shape = if v == network.start {
+            "doublecircle"
+        } else if flow_rate > 0 {
+            "circle"
+        } else {
+            "point"
         };
 
-        flow + subflow
+        dot += &format!("  {name} [label=\"{name}\\n{flow_rate}\", shape={shape}];\n");
     }
 
-    let res = traverse(26, ("AA", 1), ("AA", 1), &valves, &HashSet::new(), &graph);
+    for (idx, &i) in valves.iter().enumerate() {
+        for &j in &valves[idx + 1..] {
+            let distance = network.distances[i][j];
+
+            // Only direct tunnels in the raw graph
+            if distance == u32::MAX || (!compressed && distance != 1) {
+                continue;
+            }
+
+            let (left, right) = (network.valves[i].name, network.valves[j].name);
+
+            let mut attributes = vec![];
+            if compressed {
+                attributes.push(format!("label={distance}, weight={distance}"));
+            }
+            if let Some(&agent) = route.get(&key(left, right)) {
+                let color = ROUTE_COLORS[agent % ROUTE_COLORS.len()];
+                attributes.push(format!("color={color}, penwidth=3"));
+            }
+
+            dot += &format!("  {left} -- {right}");
+            if !attributes.is_empty() {
+                dot += &format!(" [{}]", attributes.join(", "));
+            }
+            dot += ";\n";
+        }
+    }
+
+    dot += "}\n";
+    dot
+}
+
+/// Compute the solution to part 2
+fn part_2(input: &str, agents: usize, time: u32) -> String {
+    let network = Network::new(parse_input(input), "AA");
+
+    let (res, _) = max_pressure_agents(&network, agents, time);
 
     res.to_string()
 }
@@ -212,11 +571,138 @@ fn part_2(input: &str) -> String {
 fn main() {
     let input = include_str!("input.txt");
 
+    // Options: --agents <N> and --time <MINUTES> for part 2, --schedule,
+    // --dot <PATH>, --dot-compressed <PATH> and --highlight to draw the route of part 2
+    let mut agents = 2;
+    let mut time = 26;
+    let mut show_schedule = false;
+    let mut dot_path = None;
+    let mut dot_compressed_path = None;
+    let mut highlight = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--agents" => {
+                let n = args.next().expect("--agents requires a number");
+                agents = n
+                    .parse()
+                    .ok()
+                    .filter(|&agents| agents > 0)
+                    .expect("--agents requires at least one agent");
+            }
+            "--time" => {
+                let n = args.next().expect("--time requires a number");
+                time = n.parse().expect("Invalid time");
+            }
+            "--schedule" => show_schedule = true,
+            "--dot" => dot_path = Some(args.next().expect("--dot requires a path")),
+            "--dot-compressed" => {
+                dot_compressed_path = Some(args.next().expect("--dot-compressed requires a path"))
+            }
+            "--highlight" => highlight = true,
+            _ => panic!("Unknown argument: {arg}"),
+        }
+    }
+
     println!("Advent of Code 2022 - Day 16");
 
+    if show_schedule {
+        let network = Network::new(parse_input(input), "AA");
+
+        for (agents, time) in [(1, 30), (agents, time)] {
+            let (_, orders) = max_pressure_agents(&network, agents, time);
+            println!("{}\n", Schedule::new(&network, time, &orders));
+        }
+    }
+
+    if dot_path.is_some() || dot_compressed_path.is_some() {
+        let network = Network::new(parse_input(input), "AA");
+
+        let schedule = highlight.then(|| {
+            let (_, orders) = max_pressure_agents(&network, agents, time);
+            Schedule::new(&network, time, &orders)
+        });
+
+        for (path, compressed) in [(dot_path, false), (dot_compressed_path, true)] {
+            if let Some(path) = path {
+                let dot = to_dot(&network, schedule.as_ref(), compressed);
+                std::fs::write(path, dot).expect("Could not write graph");
+            }
+        }
+    }
+
     let part_1_res = part_1(input);
     println!("Part 1: {part_1_res}");
 
-    let part_2_res = part_2(input);
+    let part_2_res = part_2(input, agents, time);
     println!("Part 2: {part_2_res}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II";
+
+    #[test]
+    fn example_part_1() {
+        assert_eq!(part_1(EXAMPLE), "1651");
+    }
+
+    #[test]
+    fn example_part_2() {
+        assert_eq!(part_2(EXAMPLE, 2, 26), "1707");
+
+        // A single agent with 30 minutes is part 1
+        assert_eq!(part_2(EXAMPLE, 1, 30), "1651");
+    }
+
+    /// The openings of the schedule add up to the pressure of the solver
+    #[test]
+    fn schedule_pressure() {
+        let network = Network::new(parse_input(EXAMPLE), "AA");
+
+        for (agents, time) in [(1, 30), (2, 26)] {
+            let (pressure, orders) = max_pressure_agents(&network, agents, time);
+            let schedule = Schedule::new(&network, time, &orders);
+
+            assert_eq!(schedule.agents.len(), agents);
+            assert_eq!(schedule.pressure(), pressure);
+        }
+    }
+
+    /// The raw graph only has the tunnels, the compressed graph connects all useful valves
+    #[test]
+    fn dot_edges() {
+        let network = Network::new(parse_input(EXAMPLE), "AA");
+        let index = |name: &str| network.valves.iter().position(|v| v.name == name).unwrap();
+
+        let edges = |dot: &str| {
+            dot.lines()
+                .filter_map(|line| line.trim().split_once(" -- "))
+                .map(|(left, right)| {
+                    let right = right.split([' ', ';']).next().unwrap();
+                    (index(left), index(right))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let raw = edges(&to_dot(&network, None, false));
+        assert_eq!(raw.len(), 10);
+        assert!(raw.iter().all(|&(i, j)| network.distances[i][j] == 1));
+
+        // Start and six valves with a flow rate, all connected
+        let compressed = edges(&to_dot(&network, None, true));
+        assert_eq!(compressed.len(), 7 * 6 / 2);
+    }
+}
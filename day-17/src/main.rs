@@ -1,139 +1,252 @@
-use std::{collections::HashSet, fmt::Display, ops::SubAssign, str::FromStr};
+use std::{collections::HashMap, fmt::Display, str::FromStr};
 
 type Pos = (usize, usize);
 
+/// A row of the chamber, every bit is one cell (bit 0 is the left wall)
+type Row = u32;
+
+/// Rock stored as bitmasks, one for every row from bottom to top
 #[derive(Debug)]
 struct Rock {
-    shape: HashSet<Pos>,
+    rows: Vec<Row>,
+    width: usize,
 }
 
 impl FromStr for Rock {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let height = s.lines().count() - 1;
-
-        let shape = s
+        // Shapes wider than a row can't be stored
+        let rows: Vec<Row> = s
             .lines()
-            .enumerate()
-            .flat_map(|(y, line)| {
+            .rev()
+            .map(|line| {
                 line.char_indices()
-                    .filter_map(|(x, c)| (c == '#').then_some((x, height - y)))
-                    .collect::<Vec<_>>()
+                    .filter(|&(_, c)| c == '#')
+                    .try_fold(0, |row: Row, (x, _)| {
+                        Some(row | Row::checked_shl(1, x.try_into().ok()?)?)
+                    })
             })
-            .collect();
+            .collect::<Option<_>>()
+            .ok_or(())?;
 
-        Ok(Rock { shape })
+        let width = rows
+            .iter()
+            .map(|row| (Row::BITS - row.leading_zeros()) as usize)
+            .max()
+            .filter(|&width| width > 0)
+            .ok_or(())?;
+
+        Ok(Rock { rows, width })
     }
 }
 
+/// Default rock shapes, separated by empty lines
+const DEFAULT_ROCKS: &str = "####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##";
+
+/// Parse rock shapes separated by empty lines
+fn parse_rocks(input: &str) -> Vec<Rock> {
+    input
+        .replace("\r\n", "\n")
+        .split("\n\n")
+        .filter(|rock| !rock.trim().is_empty())
+        .map(|rock| {
+            rock.trim_matches('\n')
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid rock shape:\n{rock}"))
+        })
+        .collect()
+}
+
 #[derive(Debug)]
 struct Stack {
-    grid: Vec<[bool; 7]>,
+    grid: Vec<Row>,
+    width: usize,
+    /// Rocks spawn this far from the left wall and above the highest rock
+    spawn: Pos,
+    /// Number of rows below the grid that were removed, because no rock can reach them anymore
+    pruned: usize,
+    /// Print every step of the falling rocks, showing at most this many rows from the top
+    playback: Option<usize>,
+    /// Number of stored rows after which unreachable rows are pruned
+    next_prune: usize,
+    /// Rows below this index were already checked by an earlier prune
+    checked: usize,
 }
 
-impl Stack {
-    fn new() -> Self {
-        Stack { grid: vec![] }
-    }
+/// Number of new rows after which unreachable rows are pruned
+const PRUNE_THRESHOLD: usize = 4096;
 
-    /// Checks if the position has a rock or is outside of the valid area
-    fn get(&self, pos: &Pos) -> bool {
-        if !(0..7).contains(&pos.0) {
-            return true;
+impl Stack {
+    fn new(width: usize, spawn: Pos) -> Self {
+        assert!(width > 0, "The chamber needs a width");
+        assert!(width <= Row::BITS as usize, "The chamber is too wide");
+
+        Stack {
+            grid: vec![],
+            width,
+            spawn,
+            pruned: 0,
+            playback: None,
+            next_prune: PRUNE_THRESHOLD,
+            checked: 0,
         }
-
-        self.grid
-            .get(pos.1)
-            .map(|&row| row[pos.0])
-            .unwrap_or_default()
     }
 
-    fn set(&mut self, pos: &Pos) {
-        if (0..7).contains(&pos.0) {
-            while pos.1 >= self.grid.len() {
-                self.grid.push([false; 7]);
-            }
+    /// Mask of a completely filled row
+    fn full_row(&self) -> Row {
+        Row::MAX >> (Row::BITS as usize - self.width)
+    }
 
-            if let Some(row) = self.grid.get_mut(pos.1) {
-                row[pos.0] = true;
-            }
+    /// Returns the row at the given height, pruned rows are completely filled
+    fn row(&self, y: usize) -> Row {
+        match y.checked_sub(self.pruned) {
+            Some(y) => self.grid.get(y).copied().unwrap_or_default(),
+            None => self.full_row(),
         }
     }
 
     /// Returns true, if some part of the rock collides
     fn rock_collision(&self, pos: &Pos, rock: &Rock) -> bool {
-        rock.shape.iter().any(|rock| {
-            let pos = (rock.0 + pos.0, rock.1 + pos.1);
-            self.get(&pos)
-        })
+        // Check the right wall, the left wall can't be passed because of the unsigned position
+        if pos.0 + rock.width > self.width {
+            return true;
+        }
+
+        rock.rows
+            .iter()
+            .enumerate()
+            .any(|(dy, row)| self.row(pos.1 + dy) & (row << pos.0) != 0)
     }
 
     /// Draws a rock at the specified position and returns the number of new lines
     fn draw_rock(&mut self, pos: &Pos, rock: &Rock) -> usize {
         let old_height = self.height();
 
-        rock.shape.iter().for_each(|rock| {
-            let pos = (rock.0 + pos.0, rock.1 + pos.1);
-            self.set(&pos);
-        });
-
-        self.height().saturating_sub(old_height)
-    }
-
-    fn height(&self) -> usize {
-        self.grid.len()
-    }
-}
+        for (dy, row) in rock.rows.iter().enumerate() {
+            let y = pos.1 + dy - self.pruned;
 
-impl Display for Stack {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for line in self.grid.iter().rev() {
-            let line = line
-                .map(|r| match r {
-                    true => '#',
-                    false => '.',
-                })
-                .iter()
-                .collect::<String>();
+            if y >= self.grid.len() {
+                self.grid.resize(y + 1, 0);
+            }
 
-            writeln!(f, "|{}|", line)?;
+            self.grid[y] |= row << pos.0;
         }
 
-        writeln!(f, "+-------+")?;
+        if self.grid.len() > self.next_prune {
+            self.prune();
+        }
 
-        Ok(())
+        self.height().saturating_sub(old_height)
     }
-}
 
-/// Compute the solution to part 1
-fn part_1(input: &str, rocks: &Vec<Rock>) -> String {
-    // Create infinite iterator from input
-    let mut wind_pattern = input.chars().cycle();
+    /// Remove all rows below the lowest cell that can still be reached from above.
+    /// One row below that cell is kept, because rocks still collide with it.
+    ///
+    /// Only the rows added since the last prune are flood filled. If the air reaches the
+    /// lowest of these rows, older rows may still be reachable and nothing is removed
+    fn prune(&mut self) {
+        let full = self.full_row();
+        let rows = &self.grid[self.checked..];
+
+        // Flood fill the air from the top of the grid downwards, one row at a time
+        let mut reachable: Vec<Row> = vec![0; rows.len() + 1];
+        reachable[rows.len()] = full;
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for y in (0..rows.len()).rev() {
+                let air = !rows[y] & full;
+
+                // Air reachable from above or below
+                let below = if y > 0 { reachable[y - 1] } else { 0 };
+                let mut row = (reachable[y] | reachable[y + 1] | below) & air;
+
+                // Spread left and right inside the row
+                loop {
+                    let next = (row | row << 1 | row >> 1) & air;
+                    if next == row {
+                        break;
+                    }
+                    row = next;
+                }
 
-    // Create new stack
-    let mut stack = Stack::new();
+                if row != reachable[y] {
+                    reachable[y] = row;
+                    changed = true;
+                }
+            }
+        }
 
-    // Drop 2022 rocks
-    for idx in 0..2022 {
-        // Rocks always start at x = 2 and y = 3 higher than the highest rock
-        let mut pos: Pos = (2, stack.height() + 3);
+        let remove = match reachable.iter().position(|&row| row != 0) {
+            Some(0) | None => 0,
+            Some(lowest) => self.checked + lowest - 1,
+        };
 
-        // Select rock
-        let rock = &rocks[idx % rocks.len()];
+        self.grid.drain(..remove);
+        self.pruned += remove;
+
+        // Wait for enough new rows, before trying again
+        self.checked = self.grid.len();
+        self.next_prune = self.grid.len() + PRUNE_THRESHOLD;
+    }
+
+    /// Drops a rock until it comes to rest and draws it into the stack.
+    /// Returns the number of new lines and the index of the last jet that pushed the rock
+    fn drop_rock(
+        &mut self,
+        rock: &Rock,
+        jets: &mut impl Iterator<Item = (usize, char)>,
+    ) -> (usize, usize) {
+        // Rocks start at the spawn offset above the highest rock
+        let mut pos: Pos = (self.spawn.0, self.height() + self.spawn.1);
+
+        self.show_step(
+            format_args!("A new rock begins falling:"),
+            Some((rock, &pos)),
+        );
 
         // Repeat until rock is dropped
-        loop {
+        let last_jet = loop {
+            let jet = jets.next().unwrap();
+
             // Shift position left or right
-            let new_pos: Pos = match wind_pattern.next() {
-                Some('<') => (pos.0.saturating_sub(1), pos.1),
-                Some('>') => (pos.0 + 1, pos.1),
+            let (new_pos, direction): (Pos, _) = match jet.1 {
+                '<' => ((pos.0.saturating_sub(1), pos.1), "left"),
+                '>' => ((pos.0 + 1, pos.1), "right"),
                 _ => panic!(),
             };
 
             // Check for collisions
-            if !stack.rock_collision(&new_pos, rock) {
+            if new_pos != pos && !self.rock_collision(&new_pos, rock) {
                 pos = new_pos;
+                self.show_step(
+                    format_args!("Jet of gas pushes rock {direction}:"),
+                    Some((rock, &pos)),
+                );
+            } else {
+                self.show_step(
+                    format_args!("Jet of gas pushes rock {direction}, but nothing happens:"),
+                    Some((rock, &pos)),
+                );
             }
 
             // If the rock can drop
@@ -142,148 +255,363 @@ fn part_1(input: &str, rocks: &Vec<Rock>) -> String {
                 let new_pos = (pos.0, pos.1 - 1);
 
                 // Check for collision
-                if !stack.rock_collision(&new_pos, rock) {
+                if !self.rock_collision(&new_pos, rock) {
                     pos = new_pos;
+                    self.show_step(format_args!("Rock falls 1 unit:"), Some((rock, &pos)));
                 } else {
                     // Break if rock can't move down
-                    break;
+                    break jet.0;
                 }
             } else {
                 // Break if rock has reached the bottom
-                break;
+                break jet.0;
             }
-        }
+        };
 
         // Insert rock into stack
-        stack.draw_rock(&pos, rock);
+        let new_rows = self.draw_rock(&pos, rock);
+        self.show_step(
+            format_args!("Rock falls 1 unit, causing it to come to rest:"),
+            None,
+        );
+
+        (new_rows, last_jet)
     }
 
-    stack.height().to_string()
+    /// Print a step of the falling rock, if playback is enabled.
+    /// The message is only formatted when it is printed
+    fn show_step(&self, message: std::fmt::Arguments, falling: Option<(&Rock, &Pos)>) {
+        if let Some(max_rows) = self.playback {
+            println!("{message}\n{}", self.render(falling, max_rows));
+        }
+    }
+
+    /// Render the top `max_rows` rows of the stack, with the falling rock drawn as '@'
+    fn render(&self, falling: Option<(&Rock, &Pos)>, max_rows: usize) -> String {
+        let top = falling.map_or(self.height(), |(rock, pos)| {
+            usize::max(self.height(), pos.1 + rock.rows.len())
+        });
+        let bottom = usize::max(top.saturating_sub(max_rows), self.pruned);
+
+        let mut output = String::new();
+
+        for y in (bottom..top).rev() {
+            let row = self.row(y);
+
+            // Part of the falling rock in this row
+            let rock_row = falling
+                .and_then(|(rock, pos)| Some(rock.rows.get(y.checked_sub(pos.1)?)? << pos.0))
+                .unwrap_or_default();
+
+            let line = (0..self.width)
+                .map(|x| {
+                    if rock_row & 1 << x != 0 {
+                        '@'
+                    } else if row & 1 << x != 0 {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+
+            output += &format!("|{line}|\n");
+        }
+
+        if bottom == 0 {
+            output += &format!("+{}+\n", "-".repeat(self.width));
+        } else if bottom == self.pruned {
+            output += &format!("|{}| ({} rows pruned)\n", "~".repeat(self.width), bottom);
+        } else {
+            output += &format!("|{}| ({} rows hidden)\n", "~".repeat(self.width), bottom);
+        }
+
+        output
+    }
+
+    fn height(&self) -> usize {
+        self.pruned + self.grid.len()
+    }
+
+    /// Depth of the highest rock in every column, measured from the top of the stack.
+    /// Columns without rocks in the top `max_depth` rows get `max_depth`
+    fn skyline(&self, max_depth: usize) -> Vec<usize> {
+        (0..self.width)
+            .map(|x| {
+                self.grid
+                    .iter()
+                    .rev()
+                    .take(max_depth)
+                    .position(|row| row & 1 << x != 0)
+                    .unwrap_or(max_depth)
+            })
+            .collect()
+    }
 }
 
-/// Compute the solution to part 2
-fn part_2(input: &str, rocks: &Vec<Rock>) -> String {
-    // Create infinite iterator from input
-    let mut wind_pattern = input.chars().enumerate().cycle();
+impl Display for Stack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(None, usize::MAX))
+    }
+}
 
-    // Create new stack
-    let mut stack = Stack::new();
+/// Settings of the chamber
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    width: usize,
+    spawn: Pos,
+}
 
-    // Keep track of started cycles ()
-    let mut wind_cycles = vec![None; input.len()];
+impl Config {
+    /// Every rock has to fit into the chamber at its spawn position
+    fn check(&self, rocks: &[Rock]) {
+        assert!(self.width > 0, "The chamber needs a width");
+
+        for (idx, rock) in rocks.iter().enumerate() {
+            assert!(
+                self.spawn.0 + rock.width <= self.width,
+                "Rock {} with width {} doesn't fit into the chamber at x = {}",
+                idx + 1,
+                rock.width,
+                self.spawn.0
+            );
+        }
+    }
+}
 
-    // Keep track of height changes for every rock
-    let mut height_deltas = vec![];
+impl Default for Config {
+    fn default() -> Self {
+        // Rocks always start at x = 2 and y = 3 higher than the highest rock
+        Self {
+            width: 7,
+            spawn: (2, 3),
+        }
+    }
+}
 
-    // Keep track of how many times we found a cycle
-    let mut matched_cycle = 0;
+/// Rows from the top that are part of the skyline used for cycle detection
+const SKYLINE_DEPTH: usize = 64;
 
-    // Number of rocks to drop
-    const ROCKS: usize = 1000000000000;
+/// State of the simulation before dropping a rock:
+/// rock index, jet index and the depth of the highest rock in every column
+type State = (usize, usize, Vec<usize>);
 
-    for idx in 0..ROCKS {
-        let rock_idx = idx % rocks.len();
+/// Calculate the height of the tower after the given number of rocks.
+/// The state before every rock is remembered. If a state repeats with the same number of rocks
+/// and the same height change as before, the tower is periodic and the rest can be computed
+fn tower_height(input: &str, rocks: &[Rock], config: Config, count: usize) -> usize {
+    // Create infinite iterator from input
+    let mut wind_pattern = input.chars().enumerate().cycle().peekable();
 
-        let mut pos: Pos = (2, stack.height() + 3);
+    // Create new stack
+    let mut stack = Stack::new(config.width, config.spawn);
 
-        let rock = &rocks[rock_idx];
+    // Height after every number of dropped rocks
+    let mut heights = vec![0];
 
-        // Find out on which wind the piece was dropped
-        let last_wind_idx = loop {
-            let wind = wind_pattern.next().unwrap();
+    // Every time a state was seen
+    let mut seen: HashMap<State, Vec<usize>> = HashMap::new();
 
-            let new_pos: Pos = match wind.1 {
-                '<' => (pos.0.saturating_sub(1), pos.1),
-                '>' => (pos.0 + 1, pos.1),
-                _ => panic!(),
-            };
+    for idx in 0..count {
+        let rock_idx = idx % rocks.len();
+        let jet_idx = wind_pattern.peek().unwrap().0;
 
-            if !stack.rock_collision(&new_pos, rock) {
-                pos = new_pos;
-            }
+        let state = (rock_idx, jet_idx, stack.skyline(SKYLINE_DEPTH));
+        let occurrences = seen.entry(state).or_default();
+        occurrences.push(idx);
 
-            if pos.1 > 0 {
-                let new_pos = (pos.0, pos.1 - 1);
+        // Only trust a cycle that was repeated with the same length and height change
+        if let [.., first, second, third] = occurrences[..] {
+            let cycle_len = third - second;
+            let cycle_height = heights[third] - heights[second];
 
-                if !stack.rock_collision(&new_pos, rock) {
-                    pos = new_pos;
-                } else {
-                    break wind.0;
-                }
-            } else {
-                break wind.0;
+            if second - first == cycle_len && heights[second] - heights[first] == cycle_height {
+                let left_to_drop = count - idx;
+
+                // Height gained by the incomplete cycle at the end
+                let rest = left_to_drop % cycle_len;
+                let incomplete_cycle_height = heights[second + rest] - heights[second];
+
+                // Result = current height + full cycles * height per cycle + incomplete cycle
+                return heights[idx]
+                    + left_to_drop / cycle_len * cycle_height
+                    + incomplete_cycle_height;
             }
-        };
+        }
 
-        // Put rock on stack and push height change
-        let new_rows = stack.draw_rock(&pos, rock);
-        height_deltas.push(new_rows);
+        stack.drop_rock(&rocks[rock_idx], &mut wind_pattern);
+        heights.push(stack.height());
+    }
 
-        // If we just dropped the first rock of the rock cycle
-        if rock_idx == 0 {
-            // Check if we already dropped the first rock in this specific wind
-            let position = &mut wind_cycles[last_wind_idx];
+    stack.height()
+}
 
-            match *position {
-                None => {
-                    // If not, reset matched_cycle and remember this index
-                    matched_cycle = 0;
-                    *position = Some(idx);
-                }
-                Some(first_idx) => {
-                    // Increase matched_cycle
-                    matched_cycle += 1;
+/// Calculate the height of the tower by dropping every single rock
+fn simulate_height(input: &str, rocks: &[Rock], config: Config, count: usize) -> usize {
+    // Create infinite iterator from input
+    let mut wind_pattern = input.chars().enumerate().cycle();
 
-                    // if multiple cycles were matched in a row
-                    if matched_cycle > 1 {
-                        // The length of the cycle
-                        let cycle_len = idx - first_idx;
+    // Create new stack
+    let mut stack = Stack::new(config.width, config.spawn);
 
-                        // The number of rocks we still have to drop
-                        let left_to_drop = ROCKS - idx - 1;
+    for idx in 0..count {
+        stack.drop_rock(&rocks[idx % rocks.len()], &mut wind_pattern);
+    }
 
-                        // Take cycle_len height deltas from the end
-                        let heights_cycle = &height_deltas[height_deltas.len() - cycle_len..];
+    stack.height()
+}
 
-                        // Sum all those heights to get the height change per cycle
-                        let height_per_cycle: usize = heights_cycle.iter().sum();
+/// Show every step of the first rocks falling through the chamber
+fn playback(input: &str, rocks: &[Rock], config: Config, count: usize, max_rows: usize) {
+    // Create infinite iterator from input
+    let mut wind_pattern = input.chars().enumerate().cycle();
 
-                        let incomplete_cycle_height: usize =
-                            heights_cycle.iter().take(left_to_drop % cycle_len).sum();
+    // Create new stack
+    let mut stack = Stack::new(config.width, config.spawn);
+    stack.playback = Some(max_rows);
 
-                        // Result = current height + full cycles * height per cycle + incomplete cycle
-                        return (stack.height()
-                            + (left_to_drop / cycle_len * height_per_cycle)
-                            + incomplete_cycle_height)
-                            .to_string();
-                    }
-                }
-            }
-        }
+    for idx in 0..count {
+        stack.drop_rock(&rocks[idx % rocks.len()], &mut wind_pattern);
     }
+}
 
-    stack.height().to_string()
+/// Compute the solution to part 1
+fn part_1(input: &str, rocks: &[Rock], config: Config) -> String {
+    tower_height(input, rocks, config, 2022).to_string()
 }
 
-fn main() {
-    let input = include_str!("input.txt");
+/// Compute the solution to part 2
+fn part_2(input: &str, rocks: &[Rock], config: Config) -> String {
+    tower_height(input, rocks, config, 1000000000000).to_string()
+}
 
-    let rocks_string = "####\n
-.#.\n###\n.#.\n
-..#\n..#\n###\n
-#\n#\n#\n#\n
-##\n##";
+fn main() {
+    // Options: --jets <PATH>, --rocks <PATH>, --width <N>, --spawn <X,Y>
+    // any number of --count <ROCKS>, --direct to simulate every rock of the counts
+    // and --playback <ROCKS> with --playback-rows <ROWS>
+    let mut jets = include_str!("input.txt").to_string();
+    let mut rocks_string = DEFAULT_ROCKS.to_string();
+    let mut config = Config::default();
+    let mut counts: Vec<usize> = vec![];
+    let mut direct = false;
+    let mut playback_rocks = None;
+    let mut playback_rows = 20;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--jets" => {
+                let path = args.next().expect("--jets requires a path");
+                jets = std::fs::read_to_string(path).expect("Could not read jet pattern");
+            }
+            "--rocks" => {
+                let path = args.next().expect("--rocks requires a path");
+                rocks_string = std::fs::read_to_string(path).expect("Could not read rock shapes");
+            }
+            "--width" => {
+                let width = args.next().expect("--width requires a number");
+                config.width = width.parse().expect("Invalid width");
+            }
+            "--spawn" => {
+                let spawn = args.next().expect("--spawn requires an offset");
+                let (x, y) = spawn.split_once(',').expect("Invalid spawn offset");
+                config.spawn = (x.parse().unwrap(), y.parse().unwrap());
+            }
+            "--count" => {
+                let count = args.next().expect("--count requires a number");
+                counts.push(count.parse().expect("Invalid number of rocks"));
+            }
+            "--direct" => direct = true,
+            "--playback" => {
+                let count = args.next().expect("--playback requires a number");
+                playback_rocks = Some(count.parse().expect("Invalid number of rocks"));
+            }
+            "--playback-rows" => {
+                let rows = args.next().expect("--playback-rows requires a number");
+                playback_rows = rows.parse().expect("Invalid number of rows");
+            }
+            _ => panic!("Unknown argument: {arg}"),
+        }
+    }
 
-    let rocks: Vec<Rock> = rocks_string
-        .split("\n\n")
-        .filter_map(|rock| rock.parse().ok())
-        .collect();
+    let input = jets.trim();
+    let rocks = parse_rocks(&rocks_string);
+    config.check(&rocks);
 
     println!("Advent of Code 2022 - Day 17");
 
-    let part_1 = part_1(input, &rocks);
+    if let Some(count) = playback_rocks {
+        playback(input, &rocks, config, count, playback_rows);
+    }
+
+    let part_1 = part_1(input, &rocks, config);
     println!("Part 1: {part_1}");
 
-    let part_2 = part_2(input, &rocks);
+    let part_2 = part_2(input, &rocks, config);
     println!("Part 2: {part_2}");
+
+    for count in counts {
+        let height = match direct {
+            true => simulate_height(input, &rocks, config, count),
+            false => tower_height(input, &rocks, config, count),
+        };
+        println!("Height after {count} rocks: {height}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Jets that always push left and flat rocks leave the right columns open down to the floor,
+    /// so nothing can be pruned
+    #[test]
+    fn open_column_is_not_pruned() {
+        let rocks = parse_rocks("####");
+        let count = 3 * PRUNE_THRESHOLD;
+
+        assert_eq!(
+            simulate_height("<", &rocks, Config::default(), count),
+            count
+        );
+    }
+
+    #[test]
+    fn rock_wider_than_a_row() {
+        assert!("#".repeat(Row::BITS as usize).parse::<Rock>().is_ok());
+        assert!("#".repeat(Row::BITS as usize + 1).parse::<Rock>().is_err());
+        assert!("...".parse::<Rock>().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit into the chamber")]
+    fn rock_sticks_out_of_the_chamber() {
+        let config = Config {
+            width: 5,
+            ..Config::default()
+        };
+
+        config.check(&parse_rocks(DEFAULT_ROCKS));
+    }
+
+    /// Pruning must not change the height of the default rocks
+    #[test]
+    fn pruning_keeps_height() {
+        let rocks = parse_rocks(DEFAULT_ROCKS);
+        let jets = include_str!("input.txt").trim();
+
+        let mut stack = Stack::new(7, (2, 3));
+        let mut wind_pattern = jets.chars().enumerate().cycle();
+
+        for idx in 0..10 * PRUNE_THRESHOLD {
+            stack.drop_rock(&rocks[idx % rocks.len()], &mut wind_pattern);
+        }
+
+        assert!(stack.pruned > 0);
+        assert!(stack.grid.len() <= 2 * PRUNE_THRESHOLD);
+        assert_eq!(
+            stack.height(),
+            tower_height(jets, &rocks, Config::default(), 10 * PRUNE_THRESHOLD)
+        );
+    }
 }
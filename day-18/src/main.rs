@@ -1,18 +1,19 @@
 use std::{
+    collections::{HashMap, HashSet},
     ops::{Index, IndexMut},
     str::FromStr,
 };
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 struct Pos {
-    x: usize,
-    y: usize,
-    z: usize,
+    x: i64,
+    y: i64,
+    z: i64,
 }
 
 impl Pos {
     /// Create new Pos
-    fn new(x: usize, y: usize, z: usize) -> Self {
+    fn new(x: i64, y: i64, z: i64) -> Self {
         Pos { x, y, z }
     }
 
@@ -24,6 +25,20 @@ impl Pos {
             z: self.z.max(other.z),
         }
     }
+
+    /// elementwise minimum
+    fn min(&self, other: &Self) -> Self {
+        Self {
+            x: self.x.min(other.x),
+            y: self.y.min(other.y),
+            z: self.z.min(other.z),
+        }
+    }
+
+    /// Add the same value to every coordinate
+    fn offset(&self, value: i64) -> Self {
+        Self::new(self.x + value, self.y + value, self.z + value)
+    }
 }
 
 impl FromStr for Pos {
@@ -32,15 +47,14 @@ impl FromStr for Pos {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parsed = s
             .split(',')
-            .map(&str::parse::<usize>)
+            .map(|v| v.trim().parse::<i64>())
             .collect::<Result<Vec<_>, _>>()
             .map_err(|_| ())?;
 
-        Ok(Pos {
-            x: parsed[0],
-            y: parsed[1],
-            z: parsed[2],
-        })
+        match parsed[..] {
+            [x, y, z] => Ok(Pos { x, y, z }),
+            _ => Err(()),
+        }
     }
 }
 
@@ -51,39 +65,306 @@ enum Material {
     Lava,
 }
 
+/// Connected air trapped inside the droplet
+#[derive(Debug)]
+struct Pocket {
+    /// Number of air cells
+    volume: usize,
+    /// Number of lava faces surrounding the pocket
+    surface: usize,
+    /// Bounding box (inclusive)
+    min: Pos,
+    max: Pos,
+}
+
+/// One side of a unit cube
+#[derive(Debug)]
+struct Face {
+    normal: Pos,
+    /// Corners relative to the cube, counter-clockwise when looking from outside
+    corners: [Pos; 4],
+}
+
+const fn p(x: i64, y: i64, z: i64) -> Pos {
+    Pos { x, y, z }
+}
+
+/// All six faces of a cube
+const FACES: [Face; 6] = [
+    Face {
+        normal: p(1, 0, 0),
+        corners: [p(1, 0, 0), p(1, 1, 0), p(1, 1, 1), p(1, 0, 1)],
+    },
+    Face {
+        normal: p(-1, 0, 0),
+        corners: [p(0, 0, 0), p(0, 0, 1), p(0, 1, 1), p(0, 1, 0)],
+    },
+    Face {
+        normal: p(0, 1, 0),
+        corners: [p(0, 1, 0), p(0, 1, 1), p(1, 1, 1), p(1, 1, 0)],
+    },
+    Face {
+        normal: p(0, -1, 0),
+        corners: [p(0, 0, 0), p(1, 0, 0), p(1, 0, 1), p(0, 0, 1)],
+    },
+    Face {
+        normal: p(0, 0, 1),
+        corners: [p(0, 0, 1), p(1, 0, 1), p(1, 1, 1), p(0, 1, 1)],
+    },
+    Face {
+        normal: p(0, 0, -1),
+        corners: [p(0, 0, 0), p(0, 1, 0), p(1, 1, 0), p(1, 0, 0)],
+    },
+];
+
+/// Sparse grid, only cells that are not air are stored.
+/// All cells inside the bounds (inclusive) are part of the grid
 #[derive(Debug)]
 struct Grid3D {
-    data: Vec<Material>,
-    width: usize,
-    height: usize,
-    depth: usize,
+    cells: HashMap<Pos, Material>,
+    min: Pos,
+    max: Pos,
 }
 
 impl Grid3D {
-    /// Create new grid with the given size
-    fn new(width: usize, height: usize, depth: usize) -> Self {
-        let data_len = width * height * depth;
-
+    /// Create new grid with the given bounds
+    fn new(min: Pos, max: Pos) -> Self {
         Self {
-            data: vec![Material::Air; data_len],
-            width,
-            height,
-            depth,
+            cells: HashMap::new(),
+            min,
+            max,
         }
     }
 
+    /// Check if the position is inside the bounds
+    fn contains(&self, pos: &Pos) -> bool {
+        (self.min.x..=self.max.x).contains(&pos.x)
+            && (self.min.y..=self.max.y).contains(&pos.y)
+            && (self.min.z..=self.max.z).contains(&pos.z)
+    }
+
     /// Return an iterator for all neighboring cells
-    fn neighbors(&self, &Pos { x, y, z }: &Pos) -> impl Iterator<Item = Pos> {
+    fn neighbors(&self, &Pos { x, y, z }: &Pos) -> impl Iterator<Item = Pos> + '_ {
         [
-            (x < self.width - 1).then_some(Pos::new(x + 1, y, z)),
-            (y < self.height - 1).then_some(Pos::new(x, y + 1, z)),
-            (z < self.depth - 1).then_some(Pos::new(x, y, z + 1)),
-            x.checked_sub(1).map(|x| Pos::new(x, y, z)),
-            y.checked_sub(1).map(|y| Pos::new(x, y, z)),
-            z.checked_sub(1).map(|z| Pos::new(x, y, z)),
+            Pos::new(x + 1, y, z),
+            Pos::new(x, y + 1, z),
+            Pos::new(x, y, z + 1),
+            Pos::new(x - 1, y, z),
+            Pos::new(x, y - 1, z),
+            Pos::new(x, y, z - 1),
         ]
         .into_iter()
-        .flatten()
+        .filter(|pos| self.contains(pos))
+    }
+
+    /// Return an iterator for all cells that share a face, edge or corner with the cell
+    fn surrounding(&self, &Pos { x, y, z }: &Pos) -> impl Iterator<Item = Pos> + '_ {
+        (-1..=1)
+            .flat_map(move |dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+            .filter(|&d| d != (0, 0, 0))
+            .map(move |(dx, dy, dz)| Pos::new(x + dx, y + dy, z + dz))
+            .filter(|pos| self.contains(pos))
+    }
+
+    /// Mark the air outside of the droplet.
+    /// Returns the number of lava faces touched by the outside air
+    ///
+    /// Only the shell of air cells around the lava (sharing a face, edge or corner with lava)
+    /// is flood filled, so the air far away from the droplet is never stored.
+    /// The shell can consist of several separate parts, e.g. for far apart cubes.
+    /// The part next to the lowest x of every lava component is outside of that component.
+    /// A ray in -x direction either leaves the bounds, or reaches the shell of another
+    /// component with a lower x, so the parts can be linked until the outside is reached
+    fn fill_outside(&mut self) -> usize {
+        let lava: Vec<Pos> = self
+            .cells
+            .iter()
+            .filter(|(_, &mat)| mat == Material::Lava)
+            .map(|(pos, _)| *pos)
+            .collect();
+
+        // Split the shell into parts connected by faces
+        let mut parts: HashMap<Pos, usize> = HashMap::new();
+        let mut part_count = 0;
+
+        for pos in &lava {
+            for seed in self.surrounding(pos) {
+                if self[&seed] != Material::Air || parts.contains_key(&seed) {
+                    continue;
+                }
+
+                parts.insert(seed, part_count);
+
+                let mut queue = vec![seed];
+                while let Some(pos) = queue.pop() {
+                    for n in self.neighbors(&pos) {
+                        if self[&n] == Material::Air
+                            && !parts.contains_key(&n)
+                            && self.surrounding(&n).any(|s| self[&s] == Material::Lava)
+                        {
+                            parts.insert(n, part_count);
+                            queue.push(n);
+                        }
+                    }
+                }
+
+                part_count += 1;
+            }
+        }
+
+        // Lowest cell of every lava component (connected by faces, edges or corners)
+        let mut visited: HashSet<Pos> = HashSet::new();
+        let mut lowest = vec![];
+
+        for &pos in &lava {
+            if !visited.insert(pos) {
+                continue;
+            }
+
+            let mut min = (pos.x, pos.y, pos.z);
+            let mut queue = vec![pos];
+            while let Some(pos) = queue.pop() {
+                min = min.min((pos.x, pos.y, pos.z));
+
+                for n in self.surrounding(&pos) {
+                    if self[&n] == Material::Lava && visited.insert(n) {
+                        queue.push(n);
+                    }
+                }
+            }
+
+            lowest.push(Pos::new(min.0, min.1, min.2));
+        }
+
+        // Link the parts along rays in -x direction
+        let mut links = vec![vec![]; part_count];
+        let mut outside = vec![false; part_count];
+
+        for pos in lowest {
+            let start = Pos::new(pos.x - 1, pos.y, pos.z);
+            let part = parts[&start];
+
+            let mut ray = start;
+            loop {
+                let next = Pos::new(ray.x - 1, ray.y, ray.z);
+
+                if !self.contains(&next) {
+                    outside[part] = true;
+                    break;
+                }
+
+                if self[&next] == Material::Lava {
+                    // The cell before the lava is part of the shell
+                    let other = parts[&ray];
+                    links[part].push(other);
+                    links[other].push(part);
+                    break;
+                }
+
+                ray = next;
+            }
+        }
+
+        // Every part linked to the outside is outside as well
+        let mut queue: Vec<usize> = (0..part_count).filter(|&p| outside[p]).collect();
+        while let Some(part) = queue.pop() {
+            for &other in &links[part] {
+                if !outside[other] {
+                    outside[other] = true;
+                    queue.push(other);
+                }
+            }
+        }
+
+        let mut faces = 0;
+
+        for (pos, part) in parts {
+            if outside[part] {
+                self[&pos] = Material::OutsideAir;
+                faces += self.count_neighbors(&pos, Material::Lava);
+            }
+        }
+
+        faces
+    }
+
+    /// Find all pockets of air trapped inside the droplet.
+    /// Requires the outside to be filled first, every remaining air cell next to lava is inside
+    fn pockets(&self) -> Vec<Pocket> {
+        let mut visited: HashSet<Pos> = HashSet::new();
+        let mut pockets = vec![];
+
+        // Every pocket touches lava, so only cells next to lava need to be checked
+        let mut seeds: Vec<Pos> = self
+            .cells
+            .iter()
+            .filter(|(_, &mat)| mat == Material::Lava)
+            .flat_map(|(pos, _)| self.neighbors(pos))
+            .filter(|pos| self[pos] == Material::Air)
+            .collect();
+        seeds.sort_by_key(|pos| (pos.z, pos.y, pos.x));
+
+        for seed in seeds {
+            if !visited.insert(seed) {
+                continue;
+            }
+
+            let mut pocket = Pocket {
+                volume: 0,
+                surface: 0,
+                min: seed,
+                max: seed,
+            };
+
+            // Flood fill the pocket
+            let mut queue = vec![seed];
+            while let Some(pos) = queue.pop() {
+                pocket.volume += 1;
+                pocket.surface += self.count_neighbors(&pos, Material::Lava);
+                pocket.min = pocket.min.min(&pos);
+                pocket.max = pocket.max.max(&pos);
+
+                for n in self.neighbors(&pos) {
+                    if self[&n] == Material::Air && visited.insert(n) {
+                        queue.push(n);
+                    }
+                }
+            }
+
+            pockets.push(pocket);
+        }
+
+        pockets
+    }
+
+    /// Return all faces of lava cells that touch a cell with a different material.
+    /// If `exterior` is set, only faces touching the outside air are returned
+    fn exposed_faces(&self, exterior: bool) -> Vec<(Pos, &'static Face)> {
+        let mut faces: Vec<_> = self
+            .cells
+            .iter()
+            .filter(|(_, &mat)| mat == Material::Lava)
+            .flat_map(|(pos, _)| FACES.iter().map(move |face| (*pos, face)))
+            .filter(|(pos, face)| {
+                let neighbor = self[&Pos::new(
+                    pos.x + face.normal.x,
+                    pos.y + face.normal.y,
+                    pos.z + face.normal.z,
+                )];
+
+                match exterior {
+                    true => neighbor == Material::OutsideAir,
+                    false => neighbor != Material::Lava,
+                }
+            })
+            .collect();
+
+        // Sort for a stable output
+        faces.sort_by_key(|(pos, face)| {
+            let n = face.normal;
+            (pos.x, pos.y, pos.z, n.x, n.y, n.z)
+        });
+        faces
     }
 
     /// Count the number of neighboring cells with the material
@@ -95,28 +376,27 @@ impl Grid3D {
     }
 }
 
+/// Parse the positions of all cubes
+fn parse_positions(input: &str) -> Result<Vec<Pos>, ()> {
+    input.lines().map(&str::parse::<Pos>).collect()
+}
+
 impl FromStr for Grid3D {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let positions = s
-            .lines()
-            .map(&str::parse::<Pos>)
-            .collect::<Result<Vec<_>, _>>()?;
+        let positions = parse_positions(s)?;
+
+        let first = positions.first().copied().unwrap_or_default();
 
-        // Find the bounds (+3 to make sure the droplet is surrounded by air)
-        let Pos {
-            x: width,
-            y: height,
-            z: depth,
-        } = positions.iter().fold(Pos::default(), |a, b| a.max(b));
+        // Find the bounds (+-1 to make sure the droplet is surrounded by air)
+        let min = positions.iter().fold(first, |a, b| a.min(b)).offset(-1);
+        let max = positions.iter().fold(first, |a, b| a.max(b)).offset(1);
 
-        let mut grid = Grid3D::new(width + 3, height + 3, depth + 3);
+        let mut grid = Grid3D::new(min, max);
 
         // Add all positions to grid
         for pos in positions {
-            // +1 to make sure the droplet is surrounded by air
-            let pos = Pos::new(pos.x + 1, pos.y + 1, pos.z + 1);
             grid[&pos] = Material::Lava;
         }
 
@@ -127,37 +407,37 @@ impl FromStr for Grid3D {
 impl Index<&Pos> for Grid3D {
     type Output = Material;
 
-    fn index(&self, Pos { x, y, z }: &Pos) -> &Self::Output {
-        &self.data[z * self.height * self.width + y * self.width + x]
+    fn index(&self, pos: &Pos) -> &Self::Output {
+        self.cells.get(pos).unwrap_or(&Material::Air)
     }
 }
 
 impl IndexMut<&Pos> for Grid3D {
-    fn index_mut(&mut self, Pos { x, y, z }: &Pos) -> &mut Self::Output {
-        &mut self.data[z * self.height * self.width + y * self.width + x]
+    fn index_mut(&mut self, pos: &Pos) -> &mut Self::Output {
+        self.cells.entry(*pos).or_insert(Material::Air)
     }
 }
 
 /// Compute the solution to part 1
 fn part_1(input: &str) -> String {
-    let grid: Grid3D = input.parse().unwrap();
-
-    let mut area = 0;
-
-    // Check every position in the grid
-    for z in 0..grid.depth {
-        for y in 0..grid.height {
-            for x in 0..grid.width {
-                let pos = Pos::new(x, y, z);
+    let positions = parse_positions(input).unwrap();
+    let cubes: HashSet<Pos> = positions.iter().copied().collect();
+
+    // Every pair of neighboring cubes hides two faces, only check the positive directions
+    // to count every pair once
+    let shared = cubes
+        .iter()
+        .flat_map(|&Pos { x, y, z }| {
+            [
+                Pos::new(x + 1, y, z),
+                Pos::new(x, y + 1, z),
+                Pos::new(x, y, z + 1),
+            ]
+        })
+        .filter(|n| cubes.contains(n))
+        .count();
 
-                // Check if it is air
-                if grid[&pos] == Material::Air {
-                    // Count the lava-tiles next to it
-                    area += grid.count_neighbors(&pos, Material::Lava);
-                }
-            }
-        }
-    }
+    let area = 6 * cubes.len() - 2 * shared;
 
     area.to_string()
 }
@@ -166,47 +446,261 @@ fn part_1(input: &str) -> String {
 fn part_2(input: &str) -> String {
     let mut grid: Grid3D = input.parse().unwrap();
 
-    // Flood fill outside
-    let mut queue = vec![Pos::default()];
+    // The flood fill counts the lava faces it touches
+    let area = grid.fill_outside();
 
-    while let Some(pos) = queue.pop() {
-        if grid[&pos] == Material::Air {
-            grid[&pos] = Material::OutsideAir;
+    area.to_string()
+}
 
-            for n in grid.neighbors(&pos) {
-                queue.push(n);
-            }
-        }
+/// Export faces as a Wavefront OBJ mesh, shared vertices are only written once
+fn to_obj(faces: &[(Pos, &Face)]) -> String {
+    let mut vertices: HashMap<Pos, usize> = HashMap::new();
+    let mut output = String::from("o droplet\n");
+    let mut face_lines = String::new();
+
+    for (pos, face) in faces {
+        let indices: Vec<String> = face
+            .corners
+            .iter()
+            .map(|corner| {
+                let vertex = Pos::new(pos.x + corner.x, pos.y + corner.y, pos.z + corner.z);
+
+                let next = vertices.len() + 1;
+                let idx = *vertices.entry(vertex).or_insert_with(|| {
+                    output += &format!("v {} {} {}\n", vertex.x, vertex.y, vertex.z);
+                    next
+                });
+
+                idx.to_string()
+            })
+            .collect();
+
+        face_lines += &format!("f {}\n", indices.join(" "));
     }
 
-    let mut area = 0;
+    output + &face_lines
+}
 
-    // Check every position in the grid
-    for z in 0..grid.depth {
-        for y in 0..grid.height {
-            for x in 0..grid.width {
-                let pos = Pos::new(x, y, z);
+/// Export faces as an ASCII STL mesh, every face is split into two triangles
+fn to_stl(faces: &[(Pos, &Face)]) -> String {
+    let mut output = String::from("solid droplet\n");
 
-                // Check if it is outside air (produced in the flood-fill above)
-                if grid[&pos] == Material::OutsideAir {
-                    // Count the lava-tiles next to it
-                    area += grid.count_neighbors(&pos, Material::Lava);
-                }
+    for (pos, face) in faces {
+        let corners = face
+            .corners
+            .map(|c| format!("{} {} {}", pos.x + c.x, pos.y + c.y, pos.z + c.z));
+        let Pos { x, y, z } = face.normal;
+
+        for [a, b, c] in [[0, 1, 2], [0, 2, 3]] {
+            output += &format!("  facet normal {x} {y} {z}\n    outer loop\n");
+            for idx in [a, b, c] {
+                output += &format!("      vertex {}\n", corners[idx]);
             }
+            output += "    endloop\n  endfacet\n";
         }
     }
 
-    area.to_string()
+    output + "endsolid droplet\n"
 }
 
 fn main() {
     let input = include_str!("input.txt");
 
+    // Options: --obj <PATH>, --stl <PATH>, --exterior to only export the outside
+    // and --pockets to list the air pockets inside the droplet
+    let mut obj_path = None;
+    let mut show_pockets = false;
+    let mut stl_path = None;
+    let mut exterior = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--obj" => obj_path = Some(args.next().expect("--obj requires a path")),
+            "--stl" => stl_path = Some(args.next().expect("--stl requires a path")),
+            "--exterior" => exterior = true,
+            "--pockets" => show_pockets = true,
+            _ => panic!("Unknown argument: {arg}"),
+        }
+    }
+
     println!("Advent of Code 2022 - Day 18");
 
+    if obj_path.is_some() || stl_path.is_some() {
+        let mut grid: Grid3D = input.parse().unwrap();
+        grid.fill_outside();
+
+        let faces = grid.exposed_faces(exterior);
+
+        if let Some(path) = obj_path {
+            std::fs::write(path, to_obj(&faces)).expect("Could not write mesh");
+        }
+
+        if let Some(path) = stl_path {
+            std::fs::write(path, to_stl(&faces)).expect("Could not write mesh");
+        }
+    }
+
+    if show_pockets {
+        let mut grid: Grid3D = input.parse().unwrap();
+        grid.fill_outside();
+
+        let pockets = grid.pockets();
+
+        for (idx, pocket) in pockets.iter().enumerate() {
+            let Pocket {
+                volume,
+                surface,
+                min,
+                max,
+            } = pocket;
+
+            println!(
+                "Pocket {}: volume {volume}, surface {surface}, x={}..={}, y={}..={}, z={}..={}",
+                idx + 1,
+                min.x,
+                max.x,
+                min.y,
+                max.y,
+                min.z,
+                max.z
+            );
+        }
+
+        let volume: usize = pockets.iter().map(|p| p.volume).sum();
+        let surface: usize = pockets.iter().map(|p| p.surface).sum();
+        println!(
+            "Trapped air: {volume} cells in {} pockets, {surface} faces",
+            pockets.len()
+        );
+    }
+
     let part_1_res = part_1(input);
     println!("Part 1: {part_1_res}");
 
     let part_2_res = part_2(input);
     println!("Part 2: {part_2_res}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flood fill every air cell inside the bounds and count the lava faces
+    fn exterior_faces_naive(input: &str) -> usize {
+        let grid: Grid3D = input.parse().unwrap();
+
+        let mut visited = HashSet::from([grid.min]);
+        let mut queue = vec![grid.min];
+        let mut faces = 0;
+
+        while let Some(pos) = queue.pop() {
+            faces += grid.count_neighbors(&pos, Material::Lava);
+
+            for n in grid.neighbors(&pos) {
+                if grid[&n] == Material::Air && visited.insert(n) {
+                    queue.push(n);
+                }
+            }
+        }
+
+        faces
+    }
+
+    /// Hollow cube with walls from 0 to `size`, an optional hole and a single cube inside
+    fn hollow_cube(size: i64, hole: bool) -> String {
+        let mut lines = vec![];
+
+        for x in 0..=size {
+            for y in 0..=size {
+                for z in 0..=size {
+                    let wall = [x, y, z].iter().any(|&v| v == 0 || v == size);
+                    let is_hole = hole && x == 0 && y == 1 && z == 1;
+
+                    if wall && !is_hole {
+                        lines.push(format!("{x},{y},{z}"));
+                    }
+                }
+            }
+        }
+
+        // Inner cube, not touching the walls
+        lines.push(format!("{0},{0},{0}", size / 2));
+        lines.join("\n")
+    }
+
+    #[test]
+    fn far_apart_cubes() {
+        let input = "1,1,1\n150,150,150\n-40,7,90";
+        let mut grid: Grid3D = input.parse().unwrap();
+
+        assert_eq!(grid.fill_outside(), 18);
+        assert!(grid.cells.len() < 100);
+    }
+
+    #[test]
+    fn nested_components() {
+        for hole in [false, true] {
+            let input = hollow_cube(6, hole);
+            let mut grid: Grid3D = input.parse().unwrap();
+
+            let faces = grid.fill_outside();
+
+            // The inner cube is only reached through the hole
+            assert_eq!(faces, exterior_faces_naive(&input));
+            assert_eq!(grid[&Pos::new(2, 3, 3)] == Material::OutsideAir, hole);
+        }
+    }
+
+    /// Every exported face is part of the surface, OBJ has one face and STL two triangles each
+    #[test]
+    fn exported_faces() {
+        let input = hollow_cube(6, false);
+        let mut grid: Grid3D = input.parse().unwrap();
+        grid.fill_outside();
+
+        for (exterior, area) in [(false, part_1(&input)), (true, part_2(&input))] {
+            let faces = grid.exposed_faces(exterior);
+            assert_eq!(faces.len().to_string(), area);
+
+            let obj = to_obj(&faces);
+            assert_eq!(
+                obj.lines().filter(|l| l.starts_with("f ")).count(),
+                faces.len()
+            );
+
+            let stl = to_stl(&faces);
+            assert_eq!(stl.matches("facet normal").count(), 2 * faces.len());
+        }
+    }
+
+    /// Faces that don't touch the outside belong to the pockets
+    #[test]
+    fn pockets_surface() {
+        for hole in [false, true] {
+            let input = hollow_cube(6, hole);
+            let mut grid: Grid3D = input.parse().unwrap();
+            grid.fill_outside();
+
+            let pockets = grid.pockets();
+            let surface: usize = pockets.iter().map(|p| p.surface).sum();
+            let volume: usize = pockets.iter().map(|p| p.volume).sum();
+
+            let part_1: usize = part_1(&input).parse().unwrap();
+            let part_2: usize = part_2(&input).parse().unwrap();
+            assert_eq!(surface, part_1 - part_2);
+
+            // The inside of the walls without the inner cube
+            let expected_volume = if hole { 0 } else { 5 * 5 * 5 - 1 };
+            assert_eq!(volume, expected_volume);
+        }
+    }
+
+    #[test]
+    fn matches_naive_flood_fill() {
+        let input = include_str!("input.txt");
+        let mut grid: Grid3D = input.parse().unwrap();
+
+        assert_eq!(grid.fill_outside(), exterior_faces_naive(input));
+    }
+}
@@ -1,207 +1,745 @@
-use std::{str::FromStr, time::Instant, vec};
+use std::{collections::HashSet, fmt::Display, str::FromStr, time::Instant};
 
 use rayon::prelude::*;
 
-#[derive(Debug, Clone, Copy)]
-struct Blueprint {
-    ore_robot_cost: Materials,
-    clay_robot_cost: Materials,
-    obsidian_robot_cost: Materials,
-    geode_robot_cost: Materials,
-}
+/// Amount of every resource, indexed like the resources of the blueprint
+type Materials = Vec<usize>;
+
+/// Number of robots for every resource, indexed like the resources of the blueprint
+type Robots = Vec<usize>;
 
-#[derive(Debug, Clone, Copy, Default)]
-struct Materials {
-    ore: usize,
-    clay: usize,
-    obsidian: usize,
-    geodes: usize,
+/// Recipe for a robot that collects one resource
+#[derive(Debug, Clone)]
+struct Recipe {
+    /// Index of the collected resource
+    robot: usize,
+    cost: Materials,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Robots {
-    ore_robot: usize,
-    clay_robot: usize,
-    obsidian_robot: usize,
-    geode_robot: usize,
+/// Blueprint with any number of resources and recipes
+#[derive(Debug, Clone)]
+struct Blueprint {
+    id: usize,
+    resources: Vec<String>,
+    /// Recipes in the order of the blueprint
+    recipes: Vec<Recipe>,
+    /// Resource we start with one robot for
+    start: usize,
+    /// Resource that should be maximized
+    target: usize,
 }
 
-impl Materials {
-    fn new(ore: usize, clay: usize, obsidian: usize, geodes: usize) -> Self {
-        Self {
-            ore,
-            clay,
-            obsidian,
-            geodes,
-        }
+impl Blueprint {
+    fn resource_index(resources: &mut Vec<String>, name: &str) -> usize {
+        resources.iter().position(|r| r == name).unwrap_or_else(|| {
+            resources.push(name.to_string());
+            resources.len() - 1
+        })
     }
 
-    fn check(&self, cost: &Self) -> bool {
-        self.ore >= cost.ore
-            && self.clay >= cost.clay
-            && self.obsidian >= cost.obsidian
-            && self.geodes >= cost.geodes
+    /// Robots of the starting resource are built independently of the greedy preference
+    fn starting_robots(&self) -> Robots {
+        let mut robots = vec![0; self.resources.len()];
+        robots[self.start] = 1;
+        robots
     }
 
-    fn sub(&mut self, cost: &Self) {
-        self.ore -= cost.ore;
-        self.clay -= cost.clay;
-        self.obsidian -= cost.obsidian;
-        self.geodes -= cost.geodes;
+    /// More robots than the most expensive recipe needs can't be used, because only one robot
+    /// can be built per minute. Robots for the target are always useful
+    fn is_useful(&self, recipe: &Recipe, robots: &Robots) -> bool {
+        if recipe.robot == self.target {
+            return true;
+        }
+
+        let max_cost = self
+            .recipes
+            .iter()
+            .filter(|other| other.robot != recipe.robot)
+            .map(|other| other.cost[recipe.robot])
+            .max()
+            .unwrap_or(0);
+
+        robots[recipe.robot] < max_cost
     }
-}
 
-impl Default for Robots {
-    fn default() -> Self {
-        Self {
-            ore_robot: 1,
-            clay_robot: 0,
-            obsidian_robot: 0,
-            geode_robot: 0,
+    /// Greedy strategy: Robots are preferred in reverse order of the blueprint (geode, obsidian,
+    /// clay), a robot is only built if no preferred robot can be built.
+    /// Robots for the starting resource (ore) can always be built
+    fn is_allowed(&self, recipe: &Recipe, materials: &Materials, robots: &Robots) -> bool {
+        let buildable = |r: &Recipe| can_afford(materials, &r.cost) && self.is_useful(r, robots);
+
+        if recipe.robot == self.start {
+            return buildable(recipe);
         }
+
+        self.recipes
+            .iter()
+            .rev()
+            .filter(|r| r.robot != self.start)
+            .find(|r| buildable(r))
+            .is_some_and(|r| r.robot == recipe.robot)
     }
 }
 
 impl FromStr for Blueprint {
     type Err = ();
 
+    /// Parse sentences like "Each obsidian robot costs 3 ore and 14 clay."
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let numbers = s
-            .split_whitespace()
-            .map(&str::parse::<usize>)
-            .filter_map(Result::ok)
+        let (id, sentences) = s.split_once(':').ok_or(())?;
+        let id = id
+            .trim()
+            .strip_prefix("Blueprint ")
+            .and_then(|id| id.parse().ok())
+            .ok_or(())?;
+
+        // Robot names are singular, resources may be plural in the costs
+        let mut resources = vec![];
+        let mut parsed = vec![];
+
+        for sentence in sentences.split('.').map(str::trim) {
+            if sentence.is_empty() {
+                continue;
+            }
+
+            let sentence = sentence.strip_prefix("Each ").ok_or(())?;
+            let (robot, costs) = sentence.split_once(" robot costs ").ok_or(())?;
+
+            let robot = Self::resource_index(&mut resources, robot);
+
+            let costs = costs
+                .split(" and ")
+                .map(|cost| {
+                    let (amount, resource) = cost.trim().split_once(' ').ok_or(())?;
+                    let amount = amount.parse::<usize>().map_err(|_| ())?;
+
+                    Ok((resource.to_string(), amount))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            parsed.push((robot, costs));
+        }
+
+        // Match costs to the robot names, a plural is only accepted for a known robot
+        let parsed = parsed
+            .into_iter()
+            .map(|(robot, costs)| {
+                let costs = costs
+                    .into_iter()
+                    .map(|(name, amount)| {
+                        let singular = name.strip_suffix('s').unwrap_or(&name);
+                        let name = match resources.contains(&name) {
+                            false if resources.iter().any(|r| r == singular) => singular,
+                            _ => &name,
+                        };
+
+                        (Self::resource_index(&mut resources, name), amount)
+                    })
+                    .collect::<Vec<_>>();
+
+                (robot, costs)
+            })
             .collect::<Vec<_>>();
 
+        // All resources are known now
+        let recipes = parsed
+            .into_iter()
+            .map(|(robot, costs)| {
+                let mut cost = vec![0; resources.len()];
+                costs.into_iter().for_each(|(r, amount)| cost[r] += amount);
+                Recipe { robot, cost }
+            })
+            .collect();
+
+        // Start with an ore robot and collect geodes, if those resources exist
+        let start = resources.iter().position(|r| r == "ore").unwrap_or(0);
+        let target = resources
+            .iter()
+            .position(|r| r == "geode")
+            .unwrap_or(resources.len().saturating_sub(1));
+
         Ok(Self {
-            ore_robot_cost: Materials::new(numbers[0], 0, 0, 0),
-            clay_robot_cost: Materials::new(numbers[1], 0, 0, 0),
-            obsidian_robot_cost: Materials::new(numbers[2], numbers[3], 0, 0),
-            geode_robot_cost: Materials::new(numbers[4], 0, numbers[5], 0),
+            id,
+            resources,
+            recipes,
+            start,
+            target,
         })
     }
 }
 
+fn can_afford(materials: &Materials, cost: &Materials) -> bool {
+    materials.iter().zip(cost).all(|(have, need)| have >= need)
+}
+
 fn run(materials: &mut Materials, robots: &Robots) {
-    materials.ore += robots.ore_robot;
-    materials.clay += robots.clay_robot;
-    materials.obsidian += robots.obsidian_robot;
-    materials.geodes += robots.geode_robot;
-}
-
-fn optimize(time: usize, materials: &Materials, robots: &Robots, blueprint: &Blueprint) -> usize {
-    if time.checked_sub(1).is_none() {
-        return materials.geodes;
-    }
-
-    let mut new_states = vec![];
-
-    if materials.check(&blueprint.geode_robot_cost) {
-        let mut materials = *materials;
-        materials.sub(&blueprint.geode_robot_cost);
-        let mut robots = *robots;
-        robots.geode_robot += 1;
-        new_states.push((materials, robots))
-    } else if materials.check(&blueprint.obsidian_robot_cost)
-        && robots.obsidian_robot < blueprint.geode_robot_cost.obsidian
-    {
-        let mut materials = *materials;
-        materials.sub(&blueprint.obsidian_robot_cost);
-        let mut robots = *robots;
-        robots.obsidian_robot += 1;
-        new_states.push((materials, robots))
-    } else if materials.check(&blueprint.clay_robot_cost)
-        && robots.clay_robot < blueprint.obsidian_robot_cost.clay
-    {
-        let mut materials = *materials;
-        materials.sub(&blueprint.clay_robot_cost);
-        let mut robots = *robots;
-        robots.clay_robot += 1;
-        new_states.push((materials, robots))
-    }
-
-    if materials.check(&blueprint.ore_robot_cost)
-        && robots.ore_robot
-            < blueprint
-                .clay_robot_cost
-                .ore
-                .max(blueprint.obsidian_robot_cost.ore)
-                .max(blueprint.geode_robot_cost.ore)
-    {
-        let mut materials = *materials;
-        materials.sub(&blueprint.ore_robot_cost);
-        let mut robots = *robots;
-        robots.ore_robot += 1;
-        new_states.push((materials, robots))
-    }
-
-    new_states.push((*materials, *robots));
-
-    // Tick state
-    new_states
-        .par_iter_mut()
-        .map(|(mat, new_bots)| {
-            run(mat, robots);
-            optimize(time - 1, mat, new_bots, blueprint)
+    materials
+        .iter_mut()
+        .zip(robots)
+        .for_each(|(material, robots)| *material += robots);
+}
+
+/// Number of minutes until the robots have produced enough materials for the cost.
+/// None if the robots don't produce a required material
+fn wait_time(materials: &Materials, robots: &Robots, cost: &Materials) -> Option<usize> {
+    materials
+        .iter()
+        .zip(robots)
+        .zip(cost)
+        .map(
+            |((&have, &produce), &need)| match need.saturating_sub(have) {
+                0 => Some(0),
+                missing if produce > 0 => Some(missing.div_ceil(produce)),
+                _ => None,
+            },
+        )
+        .try_fold(0, |wait, w| Some(usize::max(wait, w?)))
+}
+
+/// Which builds are considered by the search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    /// Never build a cheaper robot while a more valuable one can be built
+    Greedy,
+    /// Consider every robot that can be built
+    Exhaustive,
+}
+
+/// State of the search: time left, robots and materials
+type State = (usize, Robots, Materials);
+
+/// Branch and bound search for the maximum amount of the target resource.
+/// Instead of simulating every minute, the search jumps directly to the next robot build
+struct Optimizer<'a> {
+    blueprint: &'a Blueprint,
+    strategy: Strategy,
+    best: usize,
+    seen: HashSet<State>,
+    /// Total time, used to convert the remaining time to minutes
+    time: usize,
+    /// Builds leading to the current state and to the best result, as (minute, recipe index)
+    path: Vec<(usize, usize)>,
+    plan: Vec<(usize, usize)>,
+}
+
+impl<'a> Optimizer<'a> {
+    fn new(blueprint: &'a Blueprint, strategy: Strategy) -> Self {
+        Self {
+            blueprint,
+            strategy,
+            best: 0,
+            seen: HashSet::new(),
+            time: 0,
+            path: vec![],
+            plan: vec![],
+        }
+    }
+
+    /// Returns the best result and the builds that achieve it
+    fn run(mut self, time: usize) -> (usize, Vec<(usize, usize)>) {
+        let materials = vec![0; self.blueprint.resources.len()];
+        let robots = self.blueprint.starting_robots();
+
+        self.time = time;
+        self.search(time, materials, robots);
+        (self.best, self.plan)
+    }
+
+    fn search(&mut self, time: usize, materials: Materials, robots: Robots) {
+        let target = self.blueprint.target;
+
+        // Target collected if nothing else is built
+        let idle = materials[target] + robots[target] * time;
+        if idle > self.best {
+            self.best = idle;
+            self.plan = self.path.clone();
+        }
+
+        // Optimistic bound: a new target robot is built in every remaining minute
+        let bound = idle + time * time.saturating_sub(1) / 2;
+        if bound <= self.best {
+            return;
+        }
+
+        // Skip states that have already been searched
+        if !self.seen.insert((time, robots.clone(), materials.clone())) {
+            return;
+        }
+
+        let blueprint = self.blueprint;
+
+        for (idx, recipe) in blueprint.recipes.iter().enumerate() {
+            if !blueprint.is_useful(recipe, &robots) {
+                continue;
+            }
+
+            // Wait until the robot can be built, it has to be finished before the time runs out
+            let Some(wait) = wait_time(&materials, &robots, &recipe.cost) else {
+                continue;
+            };
+            if wait >= time {
+                continue;
+            }
+
+            // Materials in the minute the robot is built
+            let mut available = materials.clone();
+            for _ in 0..wait {
+                run(&mut available, &robots);
+            }
+
+            if self.strategy == Strategy::Greedy
+                && !blueprint.is_allowed(recipe, &available, &robots)
+            {
+                continue;
+            }
+
+            // Build the robot, it starts collecting in the next minute
+            available
+                .iter_mut()
+                .zip(&recipe.cost)
+                .for_each(|(material, cost)| *material -= cost);
+            run(&mut available, &robots);
+
+            let mut new_robots = robots.clone();
+            new_robots[recipe.robot] += 1;
+
+            self.path.push((self.time - time + wait + 1, idx));
+            self.search(time - wait - 1, available, new_robots);
+            self.path.pop();
+        }
+    }
+}
+
+/// Compute the maximum amount of the target resource (geodes) the blueprint can collect
+fn optimize(time: usize, blueprint: &Blueprint, strategy: Strategy) -> usize {
+    Optimizer::new(blueprint, strategy).run(time).0
+}
+
+/// How build-order reports are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportStyle {
+    /// "== Minute N ==" narrative as in the puzzle
+    Narrative,
+    /// One line per minute
+    Table,
+}
+
+/// State at the end of a single minute
+#[derive(Debug, Clone)]
+struct Minute {
+    /// Recipe index of the robot started in this minute
+    build: Option<usize>,
+    materials: Materials,
+    robots: Robots,
+}
+
+/// Optimal build order of a blueprint with the inventory after every minute
+#[derive(Debug, Clone)]
+struct Report<'a> {
+    blueprint: &'a Blueprint,
+    result: usize,
+    minutes: Vec<Minute>,
+}
+
+impl<'a> Report<'a> {
+    /// Find the optimal builds and replay them minute by minute
+    fn new(time: usize, blueprint: &'a Blueprint, strategy: Strategy) -> Self {
+        let (result, plan) = Optimizer::new(blueprint, strategy).run(time);
+
+        let mut materials = vec![0; blueprint.resources.len()];
+        let mut robots = blueprint.starting_robots();
+
+        let minutes = (1..=time)
+            .map(|minute| {
+                let build = plan
+                    .iter()
+                    .find(|(build_minute, _)| *build_minute == minute)
+                    .map(|&(_, idx)| idx);
+
+                if let Some(idx) = build {
+                    materials
+                        .iter_mut()
+                        .zip(&blueprint.recipes[idx].cost)
+                        .for_each(|(material, cost)| *material -= cost);
+                }
+
+                run(&mut materials, &robots);
+
+                if let Some(idx) = build {
+                    robots[blueprint.recipes[idx].robot] += 1;
+                }
+
+                Minute {
+                    build,
+                    materials: materials.clone(),
+                    robots: robots.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // The replayed builds have to reach the result of the search
+        let collected = minutes
+            .last()
+            .map_or(0, |minute: &Minute| minute.materials[blueprint.target]);
+        assert_eq!(collected, result, "Build order does not reach the result");
+
+        Self {
+            blueprint,
+            result,
+            minutes,
+        }
+    }
+
+    /// Robot description as in the puzzle, e.g. "ore-collecting" or "geode-cracking"
+    fn robot_name(&self, resource: usize) -> String {
+        let name = &self.blueprint.resources[resource];
+
+        if name == "geode" {
+            format!("{name}-cracking")
+        } else {
+            format!("{name}-collecting")
+        }
+    }
+
+    /// Compact table with one line per minute
+    fn table(&self) -> String {
+        let resources = &self.blueprint.resources;
+        let width = resources.iter().map(String::len).max().unwrap_or(0).max(5);
+
+        let mut lines = vec![format!(
+            "Blueprint {}: {} = {}",
+            self.blueprint.id, resources[self.blueprint.target], self.result
+        )];
+
+        let header = resources
+            .iter()
+            .map(|r| format!("{r:>width$}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        lines.push(format!("Minute  {:<width$}  {header} | {header}", "Build"));
+
+        for (idx, minute) in self.minutes.iter().enumerate() {
+            let build = minute
+                .build
+                .map(|b| resources[self.blueprint.recipes[b].robot].as_str())
+                .unwrap_or("-");
+
+            let columns = |values: &[usize]| {
+                values
+                    .iter()
+                    .map(|v| format!("{v:>width$}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+
+            lines.push(format!(
+                "{:>6}  {build:<width$}  {} | {}",
+                idx + 1,
+                columns(&minute.materials),
+                columns(&minute.robots)
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Minute-by-minute narrative in the style of the puzzle text
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let blueprint = self.blueprint;
+
+        let mut robots = blueprint.starting_robots();
+
+        writeln!(f, "== Blueprint {} ==", self.blueprint.id)?;
+
+        for (idx, minute) in self.minutes.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "== Minute {} ==", idx + 1)?;
+
+            if let Some(build) = minute.build {
+                let recipe = &blueprint.recipes[build];
+                let cost = recipe
+                    .cost
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &amount)| amount > 0)
+                    .map(|(r, amount)| format!("{amount} {}", blueprint.resources[r]))
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+
+                writeln!(
+                    f,
+                    "Spend {cost} to start building a{} {} robot.",
+                    if blueprint.resources[recipe.robot].starts_with(['a', 'e', 'i', 'o', 'u']) {
+                        "n"
+                    } else {
+                        ""
+                    },
+                    self.robot_name(recipe.robot)
+                )?;
+            }
+
+            for (resource, &count) in robots.iter().enumerate() {
+                if count == 0 {
+                    continue;
+                }
+
+                let name = &blueprint.resources[resource];
+                let total = minute.materials[resource];
+                let plural = |n: usize| if n == 1 { "" } else { "s" };
+
+                // Geodes are counted, other resources are amounts
+                let (verb, collected, now) = if name == "geode" {
+                    (
+                        "crack",
+                        format!("{name}{}", plural(count)),
+                        format!("open {name}{}", plural(total)),
+                    )
+                } else {
+                    ("collect", name.to_string(), name.to_string())
+                };
+
+                writeln!(
+                    f,
+                    "{count} {} robot{} {verb}{} {count} {collected}; you now have {total} {now}.",
+                    self.robot_name(resource),
+                    plural(count),
+                    if count == 1 { "s" } else { "" },
+                )?;
+            }
+
+            if let Some(build) = minute.build {
+                let robot = blueprint.recipes[build].robot;
+                writeln!(
+                    f,
+                    "The new {} robot is ready; you now have {} of them.",
+                    self.robot_name(robot),
+                    minute.robots[robot]
+                )?;
+            }
+
+            robots.clone_from(&minute.robots);
+        }
+
+        writeln!(f)?;
+        write!(
+            f,
+            "Result: {} = {}",
+            blueprint.resources[blueprint.target], self.result
+        )
+    }
+}
+
+/// Parse all blueprints, fails on lines that are not a blueprint
+fn parse_blueprints(input: &str) -> Vec<Blueprint> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.parse()
+                .unwrap_or_else(|_| panic!("Invalid blueprint: {line}"))
+        })
+        .collect()
+}
+
+/// Compare both strategies for every blueprint and return the blueprints where they differ,
+/// with the number of geodes of the greedy and the exhaustive search
+fn compare_strategies(input: &str, time: usize) -> Vec<(usize, usize, usize)> {
+    let blueprints = parse_blueprints(input);
+
+    blueprints
+        .par_iter()
+        .map(|blueprint| {
+            let greedy = optimize(time, blueprint, Strategy::Greedy);
+            let exhaustive = optimize(time, blueprint, Strategy::Exhaustive);
+            (blueprint.id, greedy, exhaustive)
         })
-        .max()
-        .unwrap()
+        .filter(|(_, greedy, exhaustive)| greedy != exhaustive)
+        .collect()
 }
 
 /// Compute the solution to part 1
-fn part_1(input: &str) -> String {
-    let blueprints: Vec<Blueprint> = input
-        .lines()
-        .map(&str::parse)
-        .filter_map(Result::ok)
-        .collect();
+fn part_1(input: &str, strategy: Strategy) -> String {
+    let blueprints = parse_blueprints(input);
 
     let res = blueprints
         .par_iter()
-        .enumerate()
-        .map(|(idx, blueprint)| {
-            let res =
-                optimize(24, &Materials::default(), &Robots::default(), blueprint) * (idx + 1);
-            println!("Finished Blueprint {}; Quality Level = {}", idx + 1, res);
-            res
-        })
+        .map(|blueprint| optimize(24, blueprint, strategy) * blueprint.id)
         .sum::<usize>();
 
     res.to_string()
 }
 
 /// Compute the solution to part 2
-fn part_2(input: &str) -> String {
-    let blueprints: Vec<Blueprint> = input
-        .lines()
-        .take(3)
-        .map(&str::parse)
-        .filter_map(Result::ok)
-        .collect();
+fn part_2(input: &str, strategy: Strategy) -> String {
+    let blueprints = parse_blueprints(input);
+    let blueprints = &blueprints[..blueprints.len().min(3)];
 
     let res = blueprints
         .par_iter()
-        .enumerate()
-        .map(|(idx, blueprint)| {
-            let res = optimize(32, &Materials::default(), &Robots::default(), blueprint);
-            println!("Finished Blueprint {}; Maximum Geodes = {}", idx + 1, res);
-            res
-        })
+        .map(|blueprint| optimize(32, blueprint, strategy))
         .product::<usize>();
 
     res.to_string()
 }
 
 fn main() {
-    let input = include_str!("input.txt");
+    // Options: --exhaustive to consider all builds, --compare to check the greedy strategy,
+    // --blueprints <PATH> to use modified factories, --report narrative|table and
+    // --time <MINUTES> to print the build order of every blueprint
+    let mut strategy = Strategy::Greedy;
+    let mut compare = false;
+    let mut report = None;
+    let mut report_time = 24;
+    let mut input = include_str!("input.txt").to_string();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--exhaustive" => strategy = Strategy::Exhaustive,
+            "--compare" => compare = true,
+            "--blueprints" => {
+                let path = args.next().expect("--blueprints requires a path");
+                input = std::fs::read_to_string(path).expect("Failed to read blueprints");
+            }
+            "--report" => {
+                report = match args.next().as_deref() {
+                    Some("narrative") => Some(ReportStyle::Narrative),
+                    Some("table") => Some(ReportStyle::Table),
+                    _ => panic!("--report requires either 'narrative' or 'table'"),
+                }
+            }
+            "--time" => {
+                report_time = args
+                    .next()
+                    .and_then(|time| time.parse().ok())
+                    .expect("--time requires a number of minutes");
+            }
+            _ => panic!("Unknown argument: {arg}"),
+        }
+    }
+    let input = input.as_str();
 
     println!("Advent of Code 2022 - Day 19");
-    println!("!!! THIS WILL TAKE A LOOOONG TIME !!!");
+
+    if compare {
+        for time in [24, 32] {
+            let differences = compare_strategies(input, time);
+
+            if differences.is_empty() {
+                println!("{time} minutes: Greedy strategy is optimal for every blueprint");
+            }
+
+            for (id, greedy, exhaustive) in differences {
+                println!(
+                    "{time} minutes: Blueprint {id}: greedy {greedy} geodes, exhaustive {exhaustive} geodes"
+                );
+            }
+        }
+    }
+
+    if let Some(style) = report {
+        let blueprints = parse_blueprints(input);
+
+        let reports: Vec<Report> = blueprints
+            .par_iter()
+            .map(|blueprint| Report::new(report_time, blueprint, strategy))
+            .collect();
+
+        for report in reports {
+            match style {
+                ReportStyle::Narrative => println!("{report}"),
+                ReportStyle::Table => println!("{}", report.table()),
+            }
+            println!();
+        }
+    }
 
     let start = Instant::now();
-    let part_1_res = part_1(input);
+    let part_1_res = part_1(input, strategy);
     println!("Part 1: {part_1_res}. Took {:?}", start.elapsed());
 
     let start = Instant::now();
-    let part_2_res = part_2(input);
+    let part_2_res = part_2(input, strategy);
     println!("Part 2: {part_2_res}. Took {:?}", start.elapsed());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Blueprint 1: Each ore robot costs 4 ore. \
+        Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. \
+        Each geode robot costs 2 ore and 7 obsidian.
+Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. \
+        Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
+
+    /// Results of the puzzle example
+    #[test]
+    fn example_geodes() {
+        let blueprints = parse_blueprints(EXAMPLE);
+
+        let geodes = |time, strategy| {
+            blueprints
+                .iter()
+                .map(|blueprint| optimize(time, blueprint, strategy))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(geodes(24, Strategy::Greedy), [9, 12]);
+        assert_eq!(geodes(24, Strategy::Exhaustive), [9, 12]);
+        assert_eq!(geodes(32, Strategy::Exhaustive), [56, 62]);
+
+        // Always building the geode robot first misses the best result of the first blueprint
+        assert_eq!(geodes(32, Strategy::Greedy), [54, 62]);
+    }
+
+    /// The search gives the same answers as the minute by minute search it replaced
+    #[test]
+    fn input_answers() {
+        let input = include_str!("input.txt");
+
+        assert_eq!(part_1(input, Strategy::Greedy), "2160");
+        assert_eq!(part_2(input, Strategy::Greedy), "13340");
+    }
+
+    /// Plurals are only removed once from costs, robot names are kept
+    #[test]
+    fn parse_resource_names() {
+        let blueprint: Blueprint = "Blueprint 7: Each ore robot costs 2 ores. \
+            Each glass robot costs 3 ore. Each geode robot costs 2 ore and 4 glass."
+            .parse()
+            .unwrap();
+
+        assert_eq!(blueprint.id, 7);
+        assert_eq!(blueprint.resources, ["ore", "glass", "geode"]);
+        assert_eq!(blueprint.recipes[2].cost, [2, 4, 0]);
+    }
+
+    /// The best result may need no builds at all, the report must not show a build then
+    #[test]
+    fn report_without_builds() {
+        let blueprint: Blueprint = "Blueprint 1: Each ore robot costs 23 ore.".parse().unwrap();
+        let report = Report::new(24, &blueprint, Strategy::Exhaustive);
+
+        assert_eq!(report.result, 24);
+        assert!(report.minutes.iter().all(|minute| minute.build.is_none()));
+    }
+
+    /// Replaying the build order of every blueprint reaches the optimal result
+    #[test]
+    fn report_reaches_result() {
+        let blueprints = parse_blueprints(include_str!("input.txt"));
+
+        for blueprint in &blueprints {
+            for strategy in [Strategy::Greedy, Strategy::Exhaustive] {
+                let report = Report::new(24, blueprint, strategy);
+                let last = report.minutes.last().unwrap();
+
+                assert_eq!(last.materials[blueprint.target], report.result);
+                assert_eq!(report.result, optimize(24, blueprint, strategy));
+            }
+        }
+    }
+}
@@ -1,22 +1,162 @@
-fn decrypt_coordinates<const ITERATIONS: usize>(numbers: &[isize]) -> isize {
-    // Enumerate numbers to know their original indices
-    let mut numbers = numbers.iter().enumerate().collect::<Vec<_>>();
-
-    // Count numbers
-    let len = numbers.len();
-
-    // Repeat for specified iterations
-    for _ in 0..ITERATIONS {
-        // Go through all numbers in order
-        for i in 0..len {
-            // Find number in vec
-            let idx = numbers
-                .iter()
-                .position(|(idx, _)| *idx == i)
-                .expect("Find number by original index");
-
-            // Remove from vec
-            let (orig_idx, val) = numbers.remove(idx);
+use std::time::Instant;
+
+/// Number of items per bucket after a rebuild
+const BUCKET_SIZE: usize = 64;
+
+/// List of items split into small buckets (sqrt decomposition).
+/// Positional insert and remove only move the items of a single bucket,
+/// the bucket of a position is found with a Fenwick tree over the bucket lengths
+struct BucketList {
+    buckets: Vec<Vec<usize>>,
+    /// Bucket that currently holds each item
+    bucket_of: Vec<usize>,
+    /// Fenwick tree of the bucket lengths
+    lengths: Vec<usize>,
+}
+
+impl BucketList {
+    /// Create a list of the items 0..len in order
+    fn new(len: usize) -> Self {
+        let mut list = Self {
+            buckets: vec![(0..len).collect()],
+            bucket_of: vec![0; len],
+            lengths: vec![],
+        };
+
+        list.rebuild();
+        list
+    }
+
+    /// Split all items into buckets of equal size again
+    fn rebuild(&mut self) {
+        let items = self.iter().collect::<Vec<_>>();
+
+        self.buckets = items.chunks(BUCKET_SIZE).map(<[usize]>::to_vec).collect();
+        if self.buckets.is_empty() {
+            self.buckets.push(vec![]);
+        }
+
+        self.lengths = vec![0; self.buckets.len()];
+
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            for &item in bucket {
+                self.bucket_of[item] = idx;
+            }
+
+            // Build the Fenwick tree in place
+            self.lengths[idx] += bucket.len();
+            let parent = idx | (idx + 1);
+            if parent < self.lengths.len() {
+                self.lengths[parent] += self.lengths[idx];
+            }
+        }
+    }
+
+    /// Change the length of a bucket in the Fenwick tree
+    fn update_length(&mut self, mut idx: usize, add: bool) {
+        while idx < self.lengths.len() {
+            if add {
+                self.lengths[idx] += 1;
+            } else {
+                self.lengths[idx] -= 1;
+            }
+            idx |= idx + 1;
+        }
+    }
+
+    /// Number of items in the buckets before the given bucket
+    fn items_before(&self, mut idx: usize) -> usize {
+        let mut sum = 0;
+
+        while idx > 0 {
+            sum += self.lengths[idx - 1];
+            idx &= idx - 1;
+        }
+
+        sum
+    }
+
+    /// Find the bucket and offset for inserting at a position.
+    /// Returns the first bucket that reaches the position
+    fn locate(&self, mut position: usize) -> (usize, usize) {
+        let mut idx = 0;
+        let mut step = self.lengths.len().next_power_of_two();
+
+        // Largest number of buckets with less than position items
+        while step > 0 {
+            if idx + step <= self.lengths.len() && self.lengths[idx + step - 1] < position {
+                idx += step;
+                position -= self.lengths[idx - 1];
+            }
+            step /= 2;
+        }
+
+        (idx.min(self.buckets.len() - 1), position)
+    }
+
+    /// Remove an item from the list and return its former position
+    fn remove_item(&mut self, item: usize) -> usize {
+        let idx = self.bucket_of[item];
+
+        let offset = self.buckets[idx]
+            .iter()
+            .position(|&i| i == item)
+            .expect("Find item in its bucket");
+
+        self.buckets[idx].remove(offset);
+        self.update_length(idx, false);
+
+        self.items_before(idx) + offset
+    }
+
+    /// Insert an item at the given position
+    fn insert(&mut self, position: usize, item: usize) {
+        let (idx, offset) = self.locate(position);
+
+        self.buckets[idx].insert(offset, item);
+        self.bucket_of[item] = idx;
+        self.update_length(idx, true);
+
+        // Keep buckets small, so inserts and removes stay cheap
+        if self.buckets[idx].len() > 2 * BUCKET_SIZE {
+            self.rebuild();
+        }
+    }
+
+    /// Iterate over all items in order
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.buckets.iter().flatten().copied()
+    }
+}
+
+/// Mixes a list of numbers multiplied by a decryption key
+struct Mixer {
+    numbers: Vec<isize>,
+    /// Items of the list are the original indices of the numbers
+    list: BucketList,
+    /// Offsets from the 0-value that make up the grove coordinates
+    offsets: Vec<usize>,
+    /// Number of finished rounds
+    rounds: usize,
+}
+
+impl Mixer {
+    fn new(numbers: &[isize], key: isize, offsets: &[usize]) -> Self {
+        Self {
+            numbers: numbers.iter().map(|v| v * key).collect(),
+            list: BucketList::new(numbers.len()),
+            offsets: offsets.to_vec(),
+            rounds: 0,
+        }
+    }
+
+    /// Move every number once, in their original order
+    fn mix_round(&mut self) {
+        let len = self.numbers.len();
+
+        for (i, &val) in self.numbers.iter().enumerate() {
+            // Remove from list
+            let idx = self.list.remove_item(i);
 
             // Find new index
             let new_idx = (idx as isize + val)
@@ -24,22 +164,68 @@ fn decrypt_coordinates<const ITERATIONS: usize>(numbers: &[isize]) -> isize {
                 .unsigned_abs();
 
             // Insert number at new position
-            numbers.insert(new_idx, (orig_idx, val));
+            self.list.insert(new_idx, i);
+        }
+
+        self.rounds += 1;
+    }
+
+    /// Mix the given number of rounds
+    fn mix(&mut self, rounds: usize) {
+        for _ in 0..rounds {
+            self.mix_round();
         }
     }
 
-    // Find the index of the zero-value
-    let zero_idx = numbers
-        .iter()
-        .position(|(_, &v)| v == 0)
-        .expect("Find 0-value");
+    /// Numbers in their current order, as stored in the list.
+    /// Before the first round, this is the order of the input
+    fn order(&self) -> Vec<isize> {
+        self.list.iter().map(|i| self.numbers[i]).collect()
+    }
+
+    /// Numbers in their current order, rotated to start at the 0-value.
+    /// The list is circular, so only the rotation differs from `order`
+    fn sequence(&self) -> Vec<isize> {
+        let numbers = self.order();
+
+        let zero_idx = numbers.iter().position(|&v| v == 0).expect("Find 0-value");
+
+        numbers[zero_idx..]
+            .iter()
+            .chain(&numbers[..zero_idx])
+            .copied()
+            .collect()
+    }
 
-    // Sum values of the elements that are offset by 1000, 2000 and 3000 from the 0-Element
-    [1000, 2000, 3000]
-        .iter()
-        .map(|o| (zero_idx + o) % len)
-        .map(|idx| numbers[idx].1)
-        .sum()
+    /// Sum of the values at the offsets from the 0-value
+    fn coordinates(&self) -> isize {
+        let numbers = self.sequence();
+
+        self.offsets
+            .iter()
+            .map(|o| numbers[o % numbers.len()])
+            .sum()
+    }
+}
+
+/// Generate pseudo random numbers (with a single zero) to benchmark mixing large inputs
+fn generate_numbers(count: usize) -> Vec<isize> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+
+    let mut numbers = (0..count)
+        .map(|_| {
+            // xorshift
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+
+            (state % 20001) as isize - 10000
+        })
+        .map(|v| if v == 0 { 1 } else { v })
+        .collect::<Vec<_>>();
+
+    numbers[count / 2] = 0;
+    numbers
 }
 
 /// Compute the solution to part 1
@@ -50,31 +236,212 @@ fn part_1(input: &str) -> String {
         .flat_map(&str::parse::<isize>)
         .collect::<Vec<_>>();
 
-    // Decrypt one iteration
-    decrypt_coordinates::<1>(&numbers).to_string()
+    // Decrypt one round without a key
+    let mut mixer = Mixer::new(&numbers, 1, &[1000, 2000, 3000]);
+    mixer.mix(1);
+
+    mixer.coordinates().to_string()
 }
 
 /// Compute the solution to part 2
 fn part_2(input: &str) -> String {
-    // Parse numbers and multiply by 811589153
+    // Parse numbers
     let numbers = input
         .lines()
         .flat_map(&str::parse::<isize>)
-        .map(|v| v * 811589153)
         .collect::<Vec<_>>();
 
-    // Decrypt ten iterations
-    decrypt_coordinates::<10>(&numbers).to_string()
+    // Decrypt ten rounds with the decryption key 811589153
+    let mut mixer = Mixer::new(&numbers, 811589153, &[1000, 2000, 3000]);
+    mixer.mix(10);
+
+    mixer.coordinates().to_string()
 }
 
 fn main() {
-    let input = include_str!("input.txt");
+    // Options: --bench <COUNT> to mix a large generated input, --input <PATH> to use another
+    // input, --key, --rounds, --offsets A,B,C and --show for a custom decryption
+    let mut input = include_str!("input.txt").to_string();
+    let mut bench = None;
+    let mut key = None;
+    let mut rounds = None;
+    let mut offsets = None;
+    let mut show = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bench" => {
+                bench = Some(
+                    args.next()
+                        .and_then(|count| count.parse::<usize>().ok())
+                        .expect("--bench requires a count"),
+                );
+            }
+            "--input" => {
+                let path = args.next().expect("--input requires a path");
+                input = std::fs::read_to_string(path).expect("Failed to read input");
+            }
+            "--key" => {
+                key = Some(
+                    args.next()
+                        .and_then(|key| key.parse::<isize>().ok())
+                        .expect("--key requires a number"),
+                );
+            }
+            "--rounds" => {
+                rounds = Some(
+                    args.next()
+                        .and_then(|rounds| rounds.parse::<usize>().ok())
+                        .expect("--rounds requires a number"),
+                );
+            }
+            "--offsets" => {
+                offsets = Some(
+                    args.next()
+                        .and_then(|offsets| {
+                            offsets
+                                .split(',')
+                                .map(|o| o.parse::<usize>().ok())
+                                .collect::<Option<Vec<_>>>()
+                        })
+                        .expect("--offsets requires comma separated numbers"),
+                );
+            }
+            "--show" => show = true,
+            _ => panic!("Unknown argument: {arg}"),
+        }
+    }
+    let input = input.as_str();
 
     println!("Advent of Code 2022 - Day 20");
 
+    if let Some(count) = bench {
+        let numbers = generate_numbers(count);
+
+        let start = Instant::now();
+        let mut mixer = Mixer::new(&numbers, 811589153, &[1000, 2000, 3000]);
+        mixer.mix(10);
+        let res = mixer.coordinates();
+        println!(
+            "Mixed {count} numbers 10 times: {res}. Took {:?}",
+            start.elapsed()
+        );
+    }
+
+    // Custom decryption, defaults to the settings of part 2
+    if show || key.is_some() || rounds.is_some() || offsets.is_some() {
+        let numbers = input
+            .lines()
+            .flat_map(&str::parse::<isize>)
+            .collect::<Vec<_>>();
+
+        let rounds = rounds.unwrap_or(10);
+        let mut mixer = Mixer::new(
+            &numbers,
+            key.unwrap_or(811589153),
+            offsets.as_deref().unwrap_or(&[1000, 2000, 3000]),
+        );
+
+        let print_sequence = |sequence: Vec<isize>| {
+            let sequence = sequence.iter().map(isize::to_string).collect::<Vec<_>>();
+            println!("{}", sequence.join(", "));
+        };
+
+        if show {
+            println!("Initial arrangement:");
+            print_sequence(mixer.order());
+            println!();
+        }
+
+        for _ in 0..rounds {
+            mixer.mix_round();
+
+            if show {
+                let plural = if mixer.rounds == 1 { "" } else { "s" };
+                println!("After {} round{plural} of mixing:", mixer.rounds);
+                // Like the puzzle, the listings after mixing start at the 0-value
+                print_sequence(mixer.sequence());
+                println!();
+            }
+        }
+
+        println!("Grove coordinates: {}", mixer.coordinates());
+    }
+
     let part_1_res = part_1(input);
     println!("Part 1: {part_1_res}");
 
     let part_2_res = part_2(input);
     println!("Part 2: {part_2_res}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mixing with Vec::remove and Vec::insert, returns the original indices in mixed order
+    fn mix_naive(numbers: &[isize], rounds: usize) -> Vec<usize> {
+        let len = numbers.len();
+        let mut list = (0..len).collect::<Vec<_>>();
+
+        for _ in 0..rounds {
+            for (i, &val) in numbers.iter().enumerate() {
+                let idx = list.iter().position(|&item| item == i).unwrap();
+                list.remove(idx);
+
+                let new_idx = (idx as isize + val)
+                    .rem_euclid(len as isize - 1)
+                    .unsigned_abs();
+                list.insert(new_idx, i);
+            }
+        }
+
+        list
+    }
+
+    #[test]
+    fn mixing_matches_vec() {
+        for count in [2, 7, 2 * BUCKET_SIZE + 1, 1000, 5000] {
+            for key in [1, 811589153] {
+                let numbers = generate_numbers(count)
+                    .into_iter()
+                    .map(|v| v * key)
+                    .collect::<Vec<_>>();
+
+                let mut mixer = Mixer::new(&numbers, 1, &[]);
+                mixer.mix(3);
+
+                let mixed = mixer.list.iter().collect::<Vec<_>>();
+                assert_eq!(mixed, mix_naive(&numbers, 3), "{count} numbers, key {key}");
+            }
+        }
+    }
+
+    /// Inserting many items into the same region forces buckets to be rebuilt
+    #[test]
+    fn insert_and_remove_match_vec() {
+        let count = 10 * BUCKET_SIZE;
+        let mut list = BucketList::new(count);
+        let mut expected = (0..count).collect::<Vec<_>>();
+
+        let moves = generate_numbers(20 * count);
+
+        for (step, val) in moves.into_iter().enumerate() {
+            let item = step % count;
+
+            let position = list.remove_item(item);
+            assert_eq!(expected.remove(position), item);
+
+            // Keep half of the items near the front
+            let new_position = match step % 2 {
+                0 => val.unsigned_abs() % 3,
+                _ => val.unsigned_abs() % count,
+            };
+            list.insert(new_position, item);
+            expected.insert(new_position, item);
+        }
+
+        assert_eq!(list.iter().collect::<Vec<_>>(), expected);
+    }
+}